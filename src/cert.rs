@@ -0,0 +1,142 @@
+use crate::error::{Error, Result};
+#[cfg(feature = "cert")]
+use rcgen::{
+    BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    KeyUsagePurpose,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "cert")]
+use time::{Duration, OffsetDateTime};
+
+pub const DEFAULT_HOSTS: &[&str] = &["localhost", "127.0.0.1", "::1"];
+
+/// File names written by [`DevCertificates::write_to`].
+pub const CA_CERT_FILE: &str = "ca.pem";
+pub const CA_KEY_FILE: &str = "ca-key.pem";
+pub const CERT_FILE: &str = "cert.pem";
+pub const KEY_FILE: &str = "key.pem";
+
+#[cfg(feature = "cert")]
+const CA_VALIDITY_DAYS: i64 = 10 * 365;
+// Some platforms reject server certificates valid for more than 825 days,
+// even when they chain to a locally trusted root.
+#[cfg(feature = "cert")]
+const CERT_VALIDITY_DAYS: i64 = 365;
+
+/// A self-signed development CA and a server certificate it issued.
+pub struct DevCertificates {
+    pub ca_cert_pem: String,
+    pub ca_key_pem: String,
+    /// The server certificate followed by the CA certificate.
+    pub cert_chain_pem: String,
+    pub key_pem: String,
+}
+
+impl DevCertificates {
+    /// Generates a CA and a server certificate valid for `hosts` (DNS names or IP addresses).
+    #[cfg(feature = "cert")]
+    pub fn generate(hosts: &[String]) -> Result<Self> {
+        if hosts.is_empty() {
+            return Err(Error::Validation(
+                "At least one host is required for the certificate.".into(),
+            ));
+        }
+
+        let now = OffsetDateTime::now_utc();
+
+        let ca_key = KeyPair::generate().map_err(certificate_error)?;
+        let mut ca_params =
+            CertificateParams::new(Vec::<String>::new()).map_err(certificate_error)?;
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "rocket-cli Development CA");
+        ca_params.key_usages = vec![
+            KeyUsagePurpose::KeyCertSign,
+            KeyUsagePurpose::CrlSign,
+            KeyUsagePurpose::DigitalSignature,
+        ];
+        ca_params.not_before = now - Duration::days(1);
+        ca_params.not_after = now + Duration::days(CA_VALIDITY_DAYS);
+        let ca_cert = ca_params.self_signed(&ca_key).map_err(certificate_error)?;
+
+        log::debug!("Issuing certificate for {}", hosts.join(", "));
+        let key = KeyPair::generate().map_err(certificate_error)?;
+        let mut params = CertificateParams::new(hosts.to_vec()).map_err(certificate_error)?;
+        params
+            .distinguished_name
+            .push(DnType::CommonName, &hosts[0]);
+        params.key_usages = vec![
+            KeyUsagePurpose::DigitalSignature,
+            KeyUsagePurpose::KeyEncipherment,
+        ];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        params.not_before = now - Duration::days(1);
+        params.not_after = now + Duration::days(CERT_VALIDITY_DAYS);
+        let cert = params
+            .signed_by(&key, &ca_cert, &ca_key)
+            .map_err(certificate_error)?;
+
+        Ok(Self {
+            cert_chain_pem: format!("{}{}", cert.pem(), ca_cert.pem()),
+            ca_cert_pem: ca_cert.pem(),
+            ca_key_pem: ca_key.serialize_pem(),
+            key_pem: key.serialize_pem(),
+        })
+    }
+
+    /// Generates a CA and a server certificate valid for `hosts` (DNS names or IP addresses).
+    #[cfg(not(feature = "cert"))]
+    pub fn generate(_hosts: &[String]) -> Result<Self> {
+        Err(Error::feature_disabled("Certificate generation", "cert"))
+    }
+
+    /// Writes the PEM files into `dir`, refusing to replace existing ones unless `force` is set.
+    ///
+    /// Returns the paths of the server certificate and key.
+    pub fn write_to(&self, dir: &Path, force: bool) -> Result<(PathBuf, PathBuf)> {
+        let files = [
+            (CA_CERT_FILE, &self.ca_cert_pem, false),
+            (CA_KEY_FILE, &self.ca_key_pem, true),
+            (CERT_FILE, &self.cert_chain_pem, false),
+            (KEY_FILE, &self.key_pem, true),
+        ];
+
+        let existing = files.iter().find(|(name, _, _)| dir.join(name).exists());
+        if let (false, Some((name, _, _))) = (force, existing) {
+            return Err(Error::Validation(format!(
+                "'{}' already exists. Pass --force to replace the certificates.",
+                dir.join(name).display()
+            )));
+        }
+
+        fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+        for (name, contents, private) in files {
+            let path = dir.join(name);
+            log::debug!("Writing {}", path.display());
+            fs::write(&path, contents).map_err(|e| Error::io(&path, e))?;
+            if private {
+                restrict_permissions(&path)?;
+            }
+        }
+
+        Ok((dir.join(CERT_FILE), dir.join(KEY_FILE)))
+    }
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600)).map_err(|e| Error::io(path, e))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "cert")]
+fn certificate_error(error: rcgen::Error) -> Error {
+    Error::Certificate(error.to_string())
+}
@@ -0,0 +1,188 @@
+use crate::cert::{CA_CERT_FILE, DevCertificates};
+use crate::commands::CertArgs;
+use crate::error::{Error, Result};
+use crate::output::{self, Format, Report};
+use colored::*;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, value};
+
+/// Output of `rocket-cli cert`.
+#[derive(Debug, Serialize)]
+pub struct CertReport {
+    pub ca: PathBuf,
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub hosts: Vec<String>,
+}
+
+impl Report for CertReport {
+    fn print_human(&self) {
+        println!(
+            "{}",
+            format!(
+                "Development certificate for {} written to {}.",
+                self.hosts.join(", "),
+                self.cert.display()
+            )
+            .green()
+        );
+        println!(
+            "Rocket.toml now serves HTTPS in the debug profile. Trust {} in your browser or OS to avoid certificate warnings.",
+            self.ca.display()
+        );
+    }
+}
+
+pub fn handle(args: CertArgs, format: Format) -> Result<()> {
+    let report = execute(Path::new("."), &args.dir, &args.hosts, args.force)?;
+    output::emit(format, &report);
+    Ok(())
+}
+
+/// Generates certificates into `project/dir` and points the project's `[debug.tls]` at them.
+pub fn execute(project: &Path, dir: &Path, hosts: &[String], force: bool) -> Result<CertReport> {
+    let rocket_toml = project.join("Rocket.toml");
+    let cargo_toml = project.join("Cargo.toml");
+    if !rocket_toml.exists() || !cargo_toml.exists() {
+        return Err(Error::Validation(
+            "No Rocket.toml and Cargo.toml found in the current directory. Run `rocket-cli cert` from a Rocket project."
+                .into(),
+        ));
+    }
+
+    log::info!("Generating development certificates in {}", dir.display());
+    let certificates = DevCertificates::generate(hosts)?;
+    let (cert, key) = certificates.write_to(&project.join(dir), force)?;
+
+    log::info!("Enabling TLS in {}", rocket_toml.display());
+    let cert = dir.join(cert.file_name().unwrap_or_default());
+    let key = dir.join(key.file_name().unwrap_or_default());
+    edit_toml(&rocket_toml, |doc| set_debug_tls(doc, &cert, &key))?;
+    edit_toml(&cargo_toml, enable_rocket_tls_feature)?;
+    ignore_dir(&project.join(".gitignore"), dir)?;
+
+    Ok(CertReport {
+        ca: dir.join(CA_CERT_FILE),
+        cert,
+        key,
+        hosts: hosts.to_vec(),
+    })
+}
+
+fn edit_toml(path: &Path, edit: impl FnOnce(&mut DocumentMut) -> Result<()>) -> Result<()> {
+    let contents = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    let mut doc = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| Error::Validation(format!("Failed to parse {}: {}", path.display(), e)))?;
+
+    edit(&mut doc)?;
+
+    fs::write(path, doc.to_string()).map_err(|e| Error::io(path, e))
+}
+
+/// The commented-out example `rocket-cli new` writes between `[debug]` and
+/// `[release]`, which makes it part of `[release]`'s decor.
+const COMMENTED_TLS: &str = "\
+# TLS for local HTTPS (requires Rocket's \"tls\" feature; generate with --tls)
+# [debug.tls]
+# certs = \"certs/cert.pem\"        # Path to the PEM certificate chain
+# key = \"certs/key.pem\"           # Path to the PEM private key
+";
+
+/// Sets `[debug.tls]`, dropping the commented-out example that `rocket-cli new` generates.
+fn set_debug_tls(doc: &mut DocumentMut, cert: &Path, key: &Path) -> Result<()> {
+    if let Some(release) = doc.get_mut("release").and_then(Item::as_table_mut) {
+        let prefix = release
+            .decor()
+            .prefix()
+            .and_then(|p| p.as_str())
+            .unwrap_or("");
+        if let Some(cleaned) = strip_commented_tls(prefix) {
+            release.decor_mut().set_prefix(cleaned);
+        }
+    }
+
+    let debug = doc
+        .entry("debug")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| Error::Validation("`debug` in Rocket.toml is not a table".into()))?;
+
+    let tls = debug
+        .entry("tls")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| Error::Validation("`debug.tls` in Rocket.toml is not a table".into()))?;
+
+    tls.insert("certs", value(cert.display().to_string()));
+    tls.insert("key", value(key.display().to_string()));
+    Ok(())
+}
+
+/// Removes [`COMMENTED_TLS`] and the blank line after it from `prefix`, if present.
+fn strip_commented_tls(prefix: &str) -> Option<String> {
+    let start = prefix.find(COMMENTED_TLS)?;
+    let mut end = start + COMMENTED_TLS.len();
+    if prefix[end..].starts_with('\n') {
+        end += 1;
+    }
+    Some(format!("{}{}", &prefix[..start], &prefix[end..]))
+}
+
+/// Adds `tls` to the features of the `rocket` dependency.
+fn enable_rocket_tls_feature(doc: &mut DocumentMut) -> Result<()> {
+    let dependency = doc
+        .get_mut("dependencies")
+        .and_then(|deps| deps.get_mut("rocket"))
+        .ok_or_else(|| Error::Validation("Cargo.toml does not depend on `rocket`".into()))?;
+
+    if let Some(version) = dependency.as_str().map(str::to_string) {
+        let mut table = InlineTable::new();
+        table.insert("version", version.into());
+        *dependency = value(table);
+    }
+
+    let table = dependency
+        .as_table_like_mut()
+        .ok_or_else(|| Error::Validation("Unsupported `rocket` dependency in Cargo.toml".into()))?;
+
+    let features = table
+        .entry("features")
+        .or_insert(value(Array::new()))
+        .as_array_mut()
+        .ok_or_else(|| {
+            Error::Validation("`rocket.features` in Cargo.toml is not an array".into())
+        })?;
+
+    if !features.iter().any(|f| f.as_str() == Some("tls")) {
+        features.push("tls");
+    }
+    Ok(())
+}
+
+/// Makes sure the private keys never end up in version control.
+fn ignore_dir(gitignore: &Path, dir: &Path) -> Result<()> {
+    if !gitignore.exists() {
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(gitignore).map_err(|e| Error::io(gitignore, e))?;
+    let entry = format!("/{}/", dir.display());
+    let ignored = contents.lines().any(|line| {
+        let line = line.trim().trim_start_matches('/').trim_end_matches('/');
+        line == dir.display().to_string()
+    });
+
+    if !ignored {
+        let separator = if contents.ends_with('\n') || contents.is_empty() {
+            ""
+        } else {
+            "\n"
+        };
+        fs::write(gitignore, format!("{}{}{}\n", contents, separator, entry))
+            .map_err(|e| Error::io(gitignore, e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,351 @@
+use crate::commands::{DbArgs, DbCommand, SeedArgs};
+#[cfg(feature = "mongodb")]
+use crate::db::Connection;
+use crate::db::migrations::{self, MIGRATIONS_DIR, Migration, MigrationState, MigrationStatus};
+use crate::db::seeds::{self, Admin, SEEDS_DIR, SeedOutcome};
+use crate::db::{self, AppliedMigration, IndexSpec};
+use crate::error::{Error, Result};
+use crate::output::{self, Format, Report};
+use colored::*;
+use serde::Serialize;
+use std::path::Path;
+use time::OffsetDateTime;
+
+/// Output of `rocket-cli db migrate`.
+#[derive(Debug, Serialize)]
+pub struct MigrateReport {
+    pub applied: Vec<Migration>,
+}
+
+impl Report for MigrateReport {
+    fn print_human(&self) {
+        if self.applied.is_empty() {
+            println!("{}", "Database is up to date.".green());
+            return;
+        }
+        for migration in &self.applied {
+            println!("{} {}", "Applied".green(), migration.id());
+        }
+    }
+}
+
+/// Output of `rocket-cli db rollback`.
+#[derive(Debug, Serialize)]
+pub struct RollbackReport {
+    pub reverted: Vec<AppliedMigration>,
+}
+
+impl Report for RollbackReport {
+    fn print_human(&self) {
+        if self.reverted.is_empty() {
+            println!("{}", "No applied migrations to roll back.".yellow());
+            return;
+        }
+        for migration in &self.reverted {
+            println!(
+                "{} {}_{}",
+                "Reverted".green(),
+                migration.version,
+                migration.name
+            );
+        }
+    }
+}
+
+/// Output of `rocket-cli db status`.
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub migrations: Vec<MigrationStatus>,
+}
+
+impl Report for StatusReport {
+    fn print_human(&self) {
+        if self.migrations.is_empty() {
+            println!("No migrations found in {}/.", MIGRATIONS_DIR);
+            return;
+        }
+        for migration in &self.migrations {
+            let state = match migration.state {
+                MigrationState::Applied => "applied".green(),
+                MigrationState::Pending => "pending".yellow(),
+                MigrationState::Missing => "missing".red(),
+            };
+            let applied_at = migration
+                .applied_at
+                .as_deref()
+                .map(|at| format!(" ({})", at))
+                .unwrap_or_default();
+            println!(
+                "{:<8} {}_{}{}",
+                state, migration.version, migration.name, applied_at
+            );
+        }
+    }
+}
+
+/// Output of `rocket-cli db new`.
+#[derive(Debug, Serialize)]
+pub struct NewMigrationReport {
+    pub migration: Migration,
+}
+
+impl Report for NewMigrationReport {
+    fn print_human(&self) {
+        println!(
+            "{}",
+            format!("Created {}", self.migration.up.display()).green()
+        );
+        if let Some(down) = &self.migration.down {
+            println!("{}", format!("Created {}", down.display()).green());
+        }
+    }
+}
+
+/// Output of `rocket-cli db seed`.
+#[derive(Debug, Serialize)]
+pub struct SeedReport {
+    pub seeds: Vec<SeedOutcome>,
+    pub admin: Option<AdminSeed>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminSeed {
+    pub email: String,
+    /// `false` when a user with this email already existed.
+    pub created: bool,
+}
+
+impl Report for SeedReport {
+    fn print_human(&self) {
+        if self.seeds.is_empty() && self.admin.is_none() {
+            println!("No seed files found in {}/.", SEEDS_DIR);
+        }
+        for seed in &self.seeds {
+            match seed.rows {
+                Some(rows) => println!(
+                    "{} {} ({} rows)",
+                    "Seeded".green(),
+                    seed.file.display(),
+                    rows
+                ),
+                None => println!("{} {}", "Seeded".green(), seed.file.display()),
+            }
+        }
+        match &self.admin {
+            Some(AdminSeed {
+                email,
+                created: true,
+            }) => println!("{} admin user {}", "Created".green(), email),
+            Some(AdminSeed {
+                email,
+                created: false,
+            }) => println!(
+                "{}",
+                format!("Admin user {} already exists.", email).yellow()
+            ),
+            None => {}
+        }
+    }
+}
+
+/// Output of `rocket-cli db indexes`.
+#[derive(Debug, Serialize)]
+pub struct IndexesReport {
+    pub indexes: Vec<IndexOutcome>,
+    pub validator: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexOutcome {
+    #[serde(flatten)]
+    pub spec: IndexSpec,
+    /// `false` when the index already existed.
+    pub created: bool,
+}
+
+impl Report for IndexesReport {
+    fn print_human(&self) {
+        for index in &self.indexes {
+            let state = if index.created {
+                "created".green()
+            } else {
+                "exists".normal()
+            };
+            println!(
+                "{:<8} {}.{} ({})",
+                state, index.spec.collection, index.spec.name, index.spec.field
+            );
+        }
+        if self.validator {
+            println!("{}", "Schema validation enabled on users.".green());
+        }
+    }
+}
+
+pub fn handle(args: DbArgs, format: Format) -> Result<()> {
+    let project = Path::new(".");
+    let url = args.database_url;
+
+    match args.command {
+        DbCommand::Migrate => {
+            let report = migrate(project, url.as_deref())?;
+            output::emit(format, &report);
+        }
+        DbCommand::Rollback { steps } => {
+            let report = rollback(project, url.as_deref(), steps as usize)?;
+            output::emit(format, &report);
+        }
+        DbCommand::Status => {
+            let report = status(project, url.as_deref())?;
+            output::emit(format, &report);
+        }
+        DbCommand::Seed(seed_args) => {
+            let report = seed(project, url.as_deref(), &seed_args)?;
+            output::emit(format, &report);
+        }
+        DbCommand::Indexes { validator } => {
+            let report = indexes(project, url.as_deref(), validator)?;
+            output::emit(format, &report);
+        }
+        DbCommand::New { name } => {
+            let report = new_migration(project, &name)?;
+            output::emit(format, &report);
+        }
+    }
+    Ok(())
+}
+
+fn connect(project: &Path, url: Option<&str>) -> Result<Box<dyn db::Database>> {
+    let url = match url {
+        Some(url) => url.to_string(),
+        None => db::database_url(project)?,
+    };
+    db::connect(project, &url)
+}
+
+/// Applies the project's pending migrations.
+///
+/// `url` overrides `DATABASE_URL` from the environment or `.env`.
+pub fn migrate(project: &Path, url: Option<&str>) -> Result<MigrateReport> {
+    let migrations = migrations::discover(&project.join(MIGRATIONS_DIR))?;
+    let mut db = connect(project, url)?;
+    let applied = migrations::migrate(db.as_mut(), &migrations)?;
+    Ok(MigrateReport { applied })
+}
+
+/// Reverts the `steps` most recently applied migrations.
+pub fn rollback(project: &Path, url: Option<&str>, steps: usize) -> Result<RollbackReport> {
+    let migrations = migrations::discover(&project.join(MIGRATIONS_DIR))?;
+    let mut db = connect(project, url)?;
+    let reverted = migrations::rollback(db.as_mut(), &migrations, steps)?;
+    Ok(RollbackReport { reverted })
+}
+
+/// Lists every migration and whether it has been applied.
+pub fn status(project: &Path, url: Option<&str>) -> Result<StatusReport> {
+    let migrations = migrations::discover(&project.join(MIGRATIONS_DIR))?;
+    let mut db = connect(project, url)?;
+    let migrations = migrations::status(db.as_mut(), &migrations)?;
+    Ok(StatusReport { migrations })
+}
+
+/// Runs every seed file in `seeds/`, then the admin seed when requested.
+pub fn seed(project: &Path, url: Option<&str>, args: &SeedArgs) -> Result<SeedReport> {
+    let admin = if args.admin {
+        let password = match &args.admin_password {
+            Some(password) => password.clone(),
+            None => db::env_value(project, "ADMIN_PASSWORD")?.ok_or_else(|| {
+                Error::Validation(
+                    "Pass --admin-password or set ADMIN_PASSWORD to seed an admin user.".into(),
+                )
+            })?,
+        };
+        Some(Admin {
+            username: args.admin_username.clone(),
+            email: args.admin_email.clone(),
+            password,
+        })
+    } else {
+        None
+    };
+
+    let files = seeds::discover(&project.join(SEEDS_DIR))?;
+    let url = match url {
+        Some(url) => url.to_string(),
+        None => db::database_url(project)?,
+    };
+    let mut connection = db::open(project, &url)?;
+
+    let seeds = files
+        .iter()
+        .map(|file| seeds::run(&mut connection, file))
+        .collect::<Result<Vec<_>>>()?;
+
+    let admin = match admin {
+        Some(admin) => Some(seed_admin(project, &mut connection, admin)?),
+        None => None,
+    };
+
+    Ok(SeedReport { seeds, admin })
+}
+
+#[cfg(feature = "argon2")]
+fn seed_admin(project: &Path, connection: &mut db::Connection, admin: Admin) -> Result<AdminSeed> {
+    let params = seeds::argon2_params(project)?;
+    let created = seeds::seed_admin(connection, &admin, params)?;
+    Ok(AdminSeed {
+        email: admin.email,
+        created,
+    })
+}
+
+// Every database driver enables argon2, so without one `db::open` has already failed.
+#[cfg(not(feature = "argon2"))]
+fn seed_admin(
+    _project: &Path,
+    _connection: &mut db::Connection,
+    _admin: Admin,
+) -> Result<AdminSeed> {
+    Err(Error::feature_disabled("Database", "full"))
+}
+
+/// Creates the MongoDB indexes (and optionally the validator) the mongodb template uses.
+#[cfg(feature = "mongodb")]
+pub fn indexes(project: &Path, url: Option<&str>, validator: bool) -> Result<IndexesReport> {
+    let url = match url {
+        Some(url) => url.to_string(),
+        None => db::database_url(project)?,
+    };
+    let Connection::Mongo(mongo) = db::open(project, &url)? else {
+        return Err(Error::Validation(
+            "`db indexes` is for MongoDB. SQL indexes are created by migrations.".into(),
+        ));
+    };
+
+    let indexes = mongo
+        .sync_indexes()?
+        .into_iter()
+        .map(|(spec, created)| IndexOutcome { spec, created })
+        .collect();
+    if validator {
+        mongo.apply_user_validator()?;
+    }
+
+    Ok(IndexesReport { indexes, validator })
+}
+
+/// Creates the MongoDB indexes (and optionally the validator) the mongodb template uses.
+#[cfg(not(feature = "mongodb"))]
+pub fn indexes(_project: &Path, _url: Option<&str>, _validator: bool) -> Result<IndexesReport> {
+    Err(Error::feature_disabled("MongoDB", "mongodb"))
+}
+
+/// Creates the next timestamped migration pair.
+pub fn new_migration(project: &Path, name: &str) -> Result<NewMigrationReport> {
+    let migration = migrations::create(
+        &project.join(MIGRATIONS_DIR),
+        name,
+        OffsetDateTime::now_utc(),
+    )?;
+    Ok(NewMigrationReport { migration })
+}
@@ -1,9 +1,15 @@
 pub mod add;
 pub mod build;
+pub mod cert;
+pub mod db;
 pub mod new;
+pub mod openapi;
 pub mod run;
 
+use crate::error::Result;
+use crate::output::Format;
 use clap::{Args, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
@@ -12,6 +18,15 @@ pub enum Command {
 
     /// Run the Rocket application
     Run,
+
+    /// Generate a development TLS certificate and enable HTTPS
+    Cert(CertArgs),
+
+    /// Manage database migrations
+    Db(DbArgs),
+
+    /// Work with the OpenAPI document of a project generated with --openapi
+    Openapi(OpenapiArgs),
 }
 
 #[derive(Debug, Args)]
@@ -23,6 +38,28 @@ pub struct NewArgs {
     #[arg(long, help = "Initialize a git repository")]
     pub git: bool,
 
+    /// Initial git branch name
+    #[arg(long, requires = "git", help = "Initial branch name for --git")]
+    pub branch: Option<String>,
+
+    /// Author of the initial commit
+    #[arg(
+        long,
+        requires = "git",
+        value_name = "NAME <EMAIL>",
+        help = "Author of the initial commit, e.g. \"Jane Doe <jane@example.com>\""
+    )]
+    pub git_author: Option<String>,
+
+    /// Message of the initial commit
+    #[arg(
+        long,
+        requires = "git",
+        default_value = crate::git::DEFAULT_COMMIT_MESSAGE,
+        help = "Message of the initial commit"
+    )]
+    pub git_message: String,
+
     /// Template name
     #[arg(
         long,
@@ -31,14 +68,178 @@ pub struct NewArgs {
     )]
     pub template: String,
 
+    /// Origins allowed by the generated CORS fairing
+    #[arg(
+        long = "cors-origin",
+        value_name = "ORIGIN",
+        value_delimiter = ',',
+        help = "Allowed CORS origin, e.g. https://app.example.com (repeatable; prompted for when omitted)"
+    )]
+    pub cors_origins: Vec<String>,
+
+    /// Enable TLS in the debug profile
+    #[arg(long, help = "Enable Rocket's TLS support and the [debug.tls] section")]
+    pub tls: bool,
+
+    /// Embed migrations/ and apply them at startup
+    #[arg(
+        long,
+        help = "Embed migrations/ in the binary and apply pending ones at startup (SQL templates)"
+    )]
+    pub embed_migrations: bool,
+
+    /// Generate an OpenAPI document and docs UI
+    #[arg(
+        long,
+        help = "Serve an OpenAPI 3 document at /openapi.json and a docs UI at /docs (database templates)"
+    )]
+    pub openapi: bool,
+
+    /// Worker threads
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Number of worker threads (prompted for when omitted; defaults to the CPU count)"
+    )]
+    pub workers: Option<u16>,
+
     /// List all available templates
     #[arg(long, help = "List available templates")]
     pub list: bool,
 }
 
-pub fn handle_command(cmd: Command) {
+#[derive(Debug, Args)]
+pub struct CertArgs {
+    /// Directory the certificates are written to, relative to the project
+    #[arg(
+        long,
+        default_value = "certs",
+        help = "Output directory for the certificates"
+    )]
+    pub dir: PathBuf,
+
+    /// Hosts the certificate is valid for
+    #[arg(
+        long = "host",
+        value_name = "HOST",
+        value_delimiter = ',',
+        default_values_t = crate::cert::DEFAULT_HOSTS.iter().map(|h| h.to_string()),
+        help = "DNS name or IP address the certificate is valid for (repeatable)"
+    )]
+    pub hosts: Vec<String>,
+
+    /// Replace existing certificates
+    #[arg(long, help = "Overwrite existing certificates")]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DbArgs {
+    /// Connection string, overriding DATABASE_URL
+    #[arg(
+        long,
+        global = true,
+        help = "Database URL (defaults to DATABASE_URL from the environment or .env)"
+    )]
+    pub database_url: Option<String>,
+
+    #[command(subcommand)]
+    pub command: DbCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Apply pending migrations
+    Migrate,
+
+    /// Revert the most recently applied migrations
+    Rollback {
+        /// Number of migrations to revert
+        #[arg(
+            long,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u32).range(1..),
+            help = "Number of migrations to revert"
+        )]
+        steps: u32,
+    },
+
+    /// Show applied and pending migrations
+    Status,
+
+    /// Insert seed data from seeds/ and optionally an admin user
+    Seed(SeedArgs),
+
+    /// Create the MongoDB indexes the generated code relies on
+    Indexes {
+        /// Also apply the users $jsonSchema validator
+        #[arg(
+            long,
+            help = "Also enforce the UserDocument $jsonSchema on the users collection"
+        )]
+        validator: bool,
+    },
+
+    /// Create a new timestamped migration
+    New {
+        /// Migration name, e.g. add_avatar_to_users
+        name: String,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct SeedArgs {
+    /// Also create an admin user
+    #[arg(
+        long,
+        help = "Create an admin user unless one with the same email exists"
+    )]
+    pub admin: bool,
+
+    /// Username of the admin user
+    #[arg(long, requires = "admin", default_value = "admin")]
+    pub admin_username: String,
+
+    /// Email of the admin user
+    #[arg(long, requires = "admin", default_value = "admin@example.com")]
+    pub admin_email: String,
+
+    /// Password of the admin user
+    #[arg(
+        long,
+        requires = "admin",
+        help = "Admin password (defaults to ADMIN_PASSWORD from the environment or .env)"
+    )]
+    pub admin_password: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct OpenapiArgs {
+    #[command(subcommand)]
+    pub command: OpenapiCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OpenapiCommand {
+    /// Write the OpenAPI document to a file
+    Export {
+        /// File the document is written to, relative to the project
+        #[arg(
+            long,
+            short,
+            default_value = "openapi.json",
+            help = "Output file for the OpenAPI document"
+        )]
+        output: PathBuf,
+    },
+}
+
+pub fn handle_command(cmd: Command, format: Format) -> Result<()> {
     match cmd {
-        Command::New(args) => new::handle(args),
-        Command::Run => run::execute(),
+        Command::New(args) => new::handle(args, format),
+        Command::Run => run::execute(format),
+        Command::Cert(args) => cert::handle(args, format),
+        Command::Db(args) => db::handle(args, format),
+        Command::Openapi(args) => openapi::handle(args, format),
     }
 }
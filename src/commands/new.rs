@@ -1,86 +1,298 @@
 use crate::commands::NewArgs;
-use crate::templates::load_template_files;
+use crate::commands::openapi::OPENAPI_MODULE;
+use crate::db::migrations::MIGRATIONS_DIR;
+use crate::error::{Error, Result};
+use crate::git::{self, GitOptions, GitOutcome};
+use crate::output::{self, Format, Report};
+use crate::prompt;
+use crate::scaffold::Scaffolder;
+use crate::templates::{TEMPLATES, TemplateInfo};
 use colored::*;
-use handlebars::Handlebars;
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-pub fn handle(args: NewArgs) {
-    if args.list {
+pub const DEFAULT_CORS_ORIGIN: &str = "http://localhost:3000";
+
+/// Settings for `rocket-cli new` beyond the project name.
+#[derive(Debug, Clone)]
+pub struct NewOptions {
+    pub template: String,
+    pub git: Option<GitOptions>,
+    /// Origins written to `[default.cors]`; `*` allows any origin without credentials.
+    pub cors_origins: Vec<String>,
+    /// Enables Rocket's `tls` feature and the `[debug.tls]` section.
+    pub tls: bool,
+    /// Embeds `migrations/` and runs pending migrations in the DB fairing.
+    pub embed_migrations: bool,
+    /// Serves an OpenAPI document at `/openapi.json` and a docs UI at `/docs`.
+    pub openapi: bool,
+    /// Worker threads; Rocket's default (the CPU count) when unset.
+    pub workers: Option<u16>,
+}
+
+impl Default for NewOptions {
+    fn default() -> Self {
+        Self {
+            template: "minimal".into(),
+            git: None,
+            cors_origins: vec![DEFAULT_CORS_ORIGIN.into()],
+            tls: false,
+            embed_migrations: false,
+            openapi: false,
+            workers: None,
+        }
+    }
+}
+
+/// Output of `rocket-cli new --list`.
+#[derive(Debug, Serialize)]
+pub struct TemplateList {
+    pub templates: &'static [TemplateInfo],
+}
+
+impl Report for TemplateList {
+    fn print_human(&self) {
         println!("\nAvailable Rocket templates:\n");
-        println!("  minimal     → Basic Rocket project with a single route [default]");
-        println!("  mongodb     → Rocket + MongoDB integration");
-        println!("  postgres    → Rocket + PostgreSQL");
-        println!("  mysql       → Rocket + MySQL (WIP)");
-        println!("  mssql       → Rocket + SQL Server (WIP)");
-        println!("  sqlite      → Rocket + SQLite (WIP)");
+        for template in self.templates {
+            let suffix = match (template.default, template.available) {
+                (true, _) => " [default]",
+                (false, false) => " (WIP)",
+                (false, true) => "",
+            };
+            println!(
+                "  {:<11} → {}{}",
+                template.name, template.description, suffix
+            );
+        }
         println!("\nExample: rocket new my-app --template postgres --git");
-        return;
     }
+}
 
-    if let Some(name) = args.name {
-        execute(name, args.git, args.template);
-    } else {
-        eprintln!(
+/// Output of a successful `rocket-cli new <name>`.
+#[derive(Debug, Serialize)]
+pub struct NewProject {
+    pub name: String,
+    pub path: PathBuf,
+    pub template: String,
+    pub vars: BTreeMap<String, Value>,
+    pub files: Vec<PathBuf>,
+    pub git: Option<GitOutcome>,
+}
+
+impl Report for NewProject {
+    fn print_human(&self) {
+        match &self.git {
+            Some(GitOutcome::Committed { branch, commit }) => println!(
+                "Git initialized on branch '{}' with initial commit {}.",
+                branch, commit
+            ),
+            Some(GitOutcome::Initialized { .. }) => println!("Git initialized."),
+            Some(GitOutcome::Skipped { .. }) | None => {}
+        }
+
+        println!(
             "{}",
-            "Project name is required. Use `rocket new <name>` or `rocket new --list`.".yellow()
+            format!(
+                "Project '{}' created successfully using '{}' template!",
+                self.name, self.template
+            )
+            .green()
         );
-        std::process::exit(1);
     }
 }
 
-pub fn execute(name: String, git: bool, template: String) {
-    eprintln!(
-        "Creating Rocket project -> {} using template '{}'",
-        name, template
-    );
+pub fn handle(args: NewArgs, format: Format) -> Result<()> {
+    if args.list {
+        output::emit(
+            format,
+            &TemplateList {
+                templates: TEMPLATES,
+            },
+        );
+        return Ok(());
+    }
+
+    match args.name {
+        Some(name) => {
+            log::info!(
+                "Creating Rocket project -> {} using template '{}'",
+                name,
+                args.template
+            );
+
+            let git = args.git.then_some(GitOptions {
+                branch: args.branch,
+                author: args.git_author,
+                message: args.git_message,
+            });
+
+            let cors_origins = if !args.cors_origins.is_empty() {
+                args.cors_origins
+            } else if prompt::is_interactive() {
+                prompt::ask(
+                    "Allowed CORS origins (comma-separated)",
+                    DEFAULT_CORS_ORIGIN,
+                )?
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect()
+            } else {
+                vec![DEFAULT_CORS_ORIGIN.into()]
+            };
 
+            let workers = match args.workers {
+                Some(workers) => Some(workers),
+                None if prompt::is_interactive() => {
+                    parse_workers(&prompt::ask("Worker threads", "auto")?)?
+                }
+                None => None,
+            };
+
+            let options = NewOptions {
+                template: args.template,
+                git,
+                cors_origins,
+                tls: args.tls,
+                embed_migrations: args.embed_migrations,
+                openapi: args.openapi,
+                workers,
+            };
+
+            let project = execute(name, options)?;
+            output::emit(format, &project);
+            Ok(())
+        }
+        None => Err(Error::Validation(
+            "Project name is required. Use `rocket new <name>` or `rocket new --list`.".into(),
+        )),
+    }
+}
+
+pub fn execute(name: String, options: NewOptions) -> Result<NewProject> {
+    let NewOptions {
+        template,
+        git,
+        cors_origins,
+        tls,
+        embed_migrations,
+        openapi,
+        workers,
+    } = options;
     let project_dir = Path::new(&name);
 
     if project_dir.exists() {
-        eprintln!(
-            "{}",
-            format!("Project directory '{}' already exists.", name).yellow()
-        );
-        std::process::exit(1);
+        return Err(Error::Validation(format!(
+            "Project directory '{}' already exists.",
+            name
+        )));
     }
 
-    let template_files = load_template_files(&template).unwrap_or_else(|| {
-        eprintln!("{}", format!("Rocket Template '{}' not found...", template).red());
-        std::process::exit(1);
-    });
+    if let Some(options) = &git {
+        options.validate()?;
+    }
 
-    let handlebars = Handlebars::new();
-    let mut ctx = HashMap::new();
-    ctx.insert("project_name", name.as_str());
+    for origin in &cors_origins {
+        validate_origin(origin)?;
+    }
 
-    for (relative_path, content) in template_files {
-        let rendered = handlebars.render_template(content, &ctx).unwrap();
-        let full_path = project_dir.join(relative_path);
+    // Browsers refuse credentialed requests when any origin is allowed.
+    let any_origin = cors_origins.iter().any(|origin| origin == "*");
 
-        if let Some(parent) = full_path.parent() {
-            fs::create_dir_all(parent).expect("Failed to create directories...");
-        }
+    let mut scaffolder = Scaffolder::new(&template)
+        .var("project_name", name.as_str())
+        .var("cors_origins", cors_origins)
+        .var("cors_credentials", !any_origin);
+    if let Some(workers) = workers {
+        scaffolder = scaffolder.var("workers", workers);
+    }
+    if tls {
+        scaffolder = scaffolder.feature("tls");
+    }
+    if embed_migrations {
+        scaffolder = scaffolder.feature("migrations");
+    }
+    if openapi {
+        scaffolder = scaffolder.feature("openapi");
+    }
 
-        fs::write(full_path, rendered).expect("Failed to write file...");
+    let rendered = scaffolder.render()?;
+    if embed_migrations
+        && !rendered
+            .files()
+            .iter()
+            .any(|f| f.path.starts_with(MIGRATIONS_DIR))
+    {
+        return Err(Error::Validation(format!(
+            "The '{}' template has no SQL migrations to embed.",
+            template
+        )));
+    }
+    if openapi
+        && !rendered
+            .files()
+            .iter()
+            .any(|f| f.path == Path::new(OPENAPI_MODULE))
+    {
+        return Err(Error::Validation(format!(
+            "The '{}' template does not support OpenAPI generation.",
+            template
+        )));
+    }
+    rendered.write_to(project_dir)?;
+
+    let git = git
+        .map(|options| git::init_repository(project_dir, &options))
+        .transpose()?;
+
+    Ok(NewProject {
+        path: project_dir.to_path_buf(),
+        template,
+        vars: rendered.vars().clone(),
+        files: rendered.files().iter().map(|f| f.path.clone()).collect(),
+        git,
+        name,
+    })
+}
+
+/// Parses a worker count answer; `auto` keeps Rocket's default.
+fn parse_workers(answer: &str) -> Result<Option<u16>> {
+    if answer.eq_ignore_ascii_case("auto") {
+        return Ok(None);
     }
 
-    if git {
-        std::process::Command::new("git")
-            .arg("init")
-            .arg(&name)
-            .status()
-            .expect("Failed to initialize git...");
-        println!("Git initialized.");
+    match answer.parse::<u16>() {
+        Ok(workers) if workers > 0 => Ok(Some(workers)),
+        _ => Err(Error::Validation(format!(
+            "Invalid worker count '{}'. Expected a positive number or `auto`.",
+            answer
+        ))),
+    }
+}
+
+/// Accepts `*` or a bare `scheme://host[:port]` origin, as sent in the `Origin` header.
+fn validate_origin(origin: &str) -> Result<()> {
+    if origin == "*" {
+        return Ok(());
     }
 
-    println!(
-        "{}",
-        format!(
-            "Project '{}' created successfully using '{}' template!",
-            name, template
-        )
-        .green()
-    );
+    let host = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"));
+
+    match host {
+        Some(host)
+            if !host.is_empty()
+                && !host.contains('/')
+                && !host.chars().any(|c| c.is_whitespace() || c == '"') =>
+        {
+            Ok(())
+        }
+        _ => Err(Error::Validation(format!(
+            "Invalid CORS origin '{}'. Expected `*` or e.g. `https://app.example.com`.",
+            origin
+        ))),
+    }
 }
@@ -0,0 +1,113 @@
+use crate::commands::{OpenapiArgs, OpenapiCommand};
+use crate::error::{Error, Result};
+use crate::output::{self, Format, Report};
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Spec module generated by `rocket-cli new --openapi`.
+pub const OPENAPI_MODULE: &str = "src/openapi/mod.rs";
+
+pub use crate::templates::OPENAPI_EXPORT_ENV as EXPORT_ENV;
+
+/// Output of `rocket-cli openapi export`.
+#[derive(Debug, Serialize)]
+pub struct ExportReport {
+    pub path: PathBuf,
+    /// Number of documented operations (method and path pairs).
+    pub operations: usize,
+}
+
+impl Report for ExportReport {
+    fn print_human(&self) {
+        println!(
+            "{}",
+            format!(
+                "OpenAPI document with {} operations written to {}.",
+                self.operations,
+                self.path.display()
+            )
+            .green()
+        );
+    }
+}
+
+pub fn handle(args: OpenapiArgs, format: Format) -> Result<()> {
+    match args.command {
+        OpenapiCommand::Export { output } => {
+            let report = export(Path::new("."), &output)?;
+            output::emit(format, &report);
+            Ok(())
+        }
+    }
+}
+
+/// Builds and runs the project at `project` to write its document to `project/output`.
+pub fn export(project: &Path, output: &Path) -> Result<ExportReport> {
+    if !project.join("Cargo.toml").exists() {
+        return Err(Error::Validation(
+            "No Cargo.toml found in the current directory. Run `rocket-cli openapi export` from a Rocket project."
+                .into(),
+        ));
+    }
+    if !project.join(OPENAPI_MODULE).exists() {
+        return Err(Error::Validation(format!(
+            "No {} found. Generate the project with `rocket-cli new --openapi` to document its API.",
+            OPENAPI_MODULE
+        )));
+    }
+
+    log::info!("Exporting the OpenAPI document to {}", output.display());
+
+    // The application runs from `project`, so a relative `output` resolves against it.
+    let mut cargo = Command::new("cargo");
+    cargo
+        .arg("run")
+        .current_dir(project)
+        .env(EXPORT_ENV, output);
+    if log::max_level() <= log::LevelFilter::Error {
+        cargo.arg("--quiet");
+    }
+
+    log::debug!("Running {:?}", cargo);
+    let status = cargo
+        .status()
+        .map_err(|e| Error::Cargo(format!("failed to run `cargo run`: {}", e)))?;
+    log::debug!("`cargo run` exited with {}", status);
+
+    if !status.success() {
+        return Err(Error::Cargo(format!("`cargo run` exited with {}", status)));
+    }
+
+    let path = project.join(output);
+    let contents = fs::read_to_string(&path).map_err(|e| Error::io(&path, e))?;
+    let document: Value = serde_json::from_str(&contents)
+        .map_err(|e| Error::Validation(format!("{} is not valid JSON: {}", path.display(), e)))?;
+
+    Ok(ExportReport {
+        path: output.to_path_buf(),
+        operations: count_operations(&document),
+    })
+}
+
+/// Counts the operations under `paths`, skipping non-method keys such as `parameters`.
+fn count_operations(document: &Value) -> usize {
+    const METHODS: &[&str] = &[
+        "get", "put", "post", "delete", "options", "head", "patch", "trace",
+    ];
+
+    document["paths"]
+        .as_object()
+        .map(|paths| {
+            paths
+                .values()
+                .filter_map(Value::as_object)
+                .flat_map(|item| item.keys())
+                .filter(|key| METHODS.contains(&key.as_str()))
+                .count()
+        })
+        .unwrap_or(0)
+}
@@ -1,3 +1,27 @@
-pub fn execute() {
-    println!("Launching rocket...");
-}
\ No newline at end of file
+use crate::error::Result;
+use crate::output::{self, Format, Report};
+use serde::Serialize;
+
+/// Output of `rocket-cli run`.
+#[derive(Debug, Serialize)]
+pub struct RunStatus {
+    pub status: &'static str,
+}
+
+impl Report for RunStatus {
+    fn print_human(&self) {
+        println!("Launching rocket...");
+    }
+}
+
+pub fn execute(format: Format) -> Result<()> {
+    log::info!("Running the Rocket application");
+
+    output::emit(
+        format,
+        &RunStatus {
+            status: "launching",
+        },
+    );
+    Ok(())
+}
@@ -0,0 +1,269 @@
+use crate::db::{AppliedMigration, Database};
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+/// Directory migrations are read from, relative to the project.
+pub const MIGRATIONS_DIR: &str = "migrations";
+
+/// A migration file pair: `<version>_<name>.sql` and an optional `<version>_<name>.down.sql`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Migration {
+    pub version: i64,
+    pub name: String,
+    pub up: PathBuf,
+    pub down: Option<PathBuf>,
+}
+
+impl Migration {
+    /// `<version>_<name>`, as used in log messages.
+    pub fn id(&self) -> String {
+        format!("{}_{}", self.version, self.name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Recorded as applied but no longer present in the migrations directory.
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<String>,
+}
+
+/// Reads the migrations in `dir`, ordered by version.
+///
+/// Files that don't follow the naming scheme are ignored.
+pub fn discover(dir: &Path) -> Result<Vec<Migration>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut migrations: BTreeMap<i64, Migration> = BTreeMap::new();
+    let mut downs = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(dir, e))? {
+        let path = entry.map_err(|e| Error::io(dir, e))?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        if let Some((version, _)) = file_name.strip_suffix(".down.sql").and_then(parse_stem) {
+            downs.push((version, path.clone()));
+            continue;
+        }
+
+        let Some((version, name)) = file_name.strip_suffix(".sql").and_then(parse_stem) else {
+            log::debug!("Skipping {}", path.display());
+            continue;
+        };
+
+        if let Some(existing) = migrations.get(&version) {
+            return Err(Error::Validation(format!(
+                "Migrations '{}' and '{}' share version {}.",
+                existing.up.display(),
+                path.display(),
+                version
+            )));
+        }
+
+        migrations.insert(
+            version,
+            Migration {
+                version,
+                name,
+                up: path,
+                down: None,
+            },
+        );
+    }
+
+    for (version, path) in downs {
+        match migrations.get_mut(&version) {
+            Some(migration) => migration.down = Some(path),
+            None => {
+                return Err(Error::Validation(format!(
+                    "'{}' has no matching up migration.",
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    Ok(migrations.into_values().collect())
+}
+
+fn parse_stem(stem: &str) -> Option<(i64, String)> {
+    let (version, name) = stem.split_once('_')?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) || name.is_empty() {
+        return None;
+    }
+    Some((version.parse().ok()?, name.to_string()))
+}
+
+/// Creates an empty `<timestamp>_<name>.sql` and its `.down.sql` in `dir`.
+pub fn create(dir: &Path, name: &str, now: OffsetDateTime) -> Result<Migration> {
+    let name = snake_case(name);
+    if name.is_empty() {
+        return Err(Error::Validation(
+            "Migration names must contain at least one letter or digit.".into(),
+        ));
+    }
+
+    let timestamp = format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        now.year(),
+        u8::from(now.month()),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+
+    // Never sort before an existing migration, even if the clock disagrees.
+    let latest = discover(dir)?.last().map(|m| m.version).unwrap_or(0);
+    let version = timestamp.parse::<i64>().unwrap_or(0).max(latest + 1);
+
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    let up = dir.join(format!("{}_{}.sql", version, name));
+    let down = dir.join(format!("{}_{}.down.sql", version, name));
+
+    for (path, contents) in [
+        (&up, format!("-- Migration: {}\n", name)),
+        (&down, format!("-- Revert: {}\n", name)),
+    ] {
+        log::debug!("Writing {}", path.display());
+        fs::write(path, contents).map_err(|e| Error::io(path, e))?;
+    }
+
+    Ok(Migration {
+        version,
+        name,
+        up,
+        down: Some(down),
+    })
+}
+
+fn snake_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Applies every pending migration in order, returning the ones that ran.
+///
+/// Stops at the first failure; migrations applied before it stay applied.
+pub fn migrate(db: &mut dyn Database, migrations: &[Migration]) -> Result<Vec<Migration>> {
+    db.ensure_migrations_table()?;
+    let applied = db.applied_migrations()?;
+
+    let mut ran = Vec::new();
+    for migration in migrations {
+        if applied.iter().any(|a| a.version == migration.version) {
+            continue;
+        }
+
+        log::info!("Applying {}", migration.id());
+        let sql = fs::read_to_string(&migration.up).map_err(|e| Error::io(&migration.up, e))?;
+        db.apply(migration.version, &migration.name, &sql)
+            .map_err(|e| in_migration(&migration.id(), e))?;
+        ran.push(migration.clone());
+    }
+
+    Ok(ran)
+}
+
+/// Reverts the `steps` most recently applied migrations using their down files.
+pub fn rollback(
+    db: &mut dyn Database,
+    migrations: &[Migration],
+    steps: usize,
+) -> Result<Vec<AppliedMigration>> {
+    db.ensure_migrations_table()?;
+    let mut applied = db.applied_migrations()?;
+    applied.reverse();
+    applied.truncate(steps);
+
+    // Check every down file up front so a missing one doesn't leave a partial rollback.
+    let mut plan = Vec::new();
+    for record in &applied {
+        let down = migrations
+            .iter()
+            .find(|m| m.version == record.version)
+            .and_then(|m| m.down.clone())
+            .ok_or_else(|| {
+                Error::Validation(format!(
+                    "Cannot roll back {}_{}: no .down.sql file found.",
+                    record.version, record.name
+                ))
+            })?;
+        plan.push((record, down));
+    }
+
+    for (record, down) in plan {
+        log::info!("Reverting {}_{}", record.version, record.name);
+        let sql = fs::read_to_string(&down).map_err(|e| Error::io(&down, e))?;
+        db.revert(record.version, &sql)
+            .map_err(|e| in_migration(&format!("{}_{}", record.version, record.name), e))?;
+    }
+
+    Ok(applied)
+}
+
+/// Lists local and recorded migrations with their state, ordered by version.
+pub fn status(db: &mut dyn Database, migrations: &[Migration]) -> Result<Vec<MigrationStatus>> {
+    db.ensure_migrations_table()?;
+    let applied = db.applied_migrations()?;
+
+    let mut statuses: BTreeMap<i64, MigrationStatus> = migrations
+        .iter()
+        .map(|m| {
+            (
+                m.version,
+                MigrationStatus {
+                    version: m.version,
+                    name: m.name.clone(),
+                    state: MigrationState::Pending,
+                    applied_at: None,
+                },
+            )
+        })
+        .collect();
+
+    for record in applied {
+        let status = statuses
+            .entry(record.version)
+            .or_insert_with(|| MigrationStatus {
+                version: record.version,
+                name: record.name.clone(),
+                state: MigrationState::Missing,
+                applied_at: None,
+            });
+        if status.state == MigrationState::Pending {
+            status.state = MigrationState::Applied;
+        }
+        status.applied_at = Some(record.applied_at);
+    }
+
+    Ok(statuses.into_values().collect())
+}
+
+fn in_migration(id: &str, error: Error) -> Error {
+    match error {
+        Error::Database(message) => Error::Database(format!("{}: {}", id, message)),
+        other => other,
+    }
+}
@@ -0,0 +1,191 @@
+pub mod migrations;
+#[cfg(feature = "mongodb")]
+mod mongo;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod schema;
+pub mod seeds;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::path::Path;
+
+#[cfg(feature = "mongodb")]
+pub use mongo::{Mongo, user_validator};
+pub use schema::{FieldSpec, INDEXES, IndexSpec, USER_FIELDS, user_schema};
+
+/// Environment variable holding the connection string, as in the generated `.env`.
+pub const DATABASE_URL_ENV: &str = "DATABASE_URL";
+
+/// Environment variable holding the MongoDB database name, as in the generated `.env`.
+pub const DATABASE_NAME_ENV: &str = "DATABASE";
+
+/// Table recording which migrations have been applied.
+///
+/// Generated applications read the same table when they run migrations at ignition.
+pub const MIGRATIONS_TABLE: &str = "schema_migrations";
+
+/// A row of the migrations table.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: String,
+}
+
+/// A SQL database migrations and seeds can be applied to.
+pub trait Database {
+    /// Creates the migrations table if it does not exist yet.
+    fn ensure_migrations_table(&mut self) -> Result<()>;
+
+    /// Applied migrations, ordered by version.
+    fn applied_migrations(&mut self) -> Result<Vec<AppliedMigration>>;
+
+    /// Runs `sql` and records the migration in a single transaction.
+    fn apply(&mut self, version: i64, name: &str, sql: &str) -> Result<()>;
+
+    /// Runs `sql` and removes the migration's record in a single transaction.
+    fn revert(&mut self, version: i64, sql: &str) -> Result<()>;
+
+    /// Runs a script of one or more statements in a single transaction.
+    fn execute_script(&mut self, sql: &str) -> Result<()>;
+
+    /// Runs a single statement, returning the number of affected rows.
+    fn execute(&mut self, sql: &str) -> Result<u64>;
+}
+
+/// An open connection to the project's database.
+pub enum Connection {
+    Sql(Box<dyn Database>),
+    #[cfg(feature = "mongodb")]
+    Mongo(Mongo),
+}
+
+/// Opens a connection for `url`, resolving relative SQLite paths against `project`.
+///
+/// Supported schemes are `sqlite:`, `postgres://` (or `postgresql://`) and
+/// `mongodb://` (or `mongodb+srv://`).
+pub fn open(project: &Path, url: &str) -> Result<Connection> {
+    if let Some(db) = open_sql(project, url)? {
+        return Ok(Connection::Sql(db));
+    }
+
+    if is_mongo(url) {
+        return connect_mongo(project, url);
+    }
+
+    Err(unsupported_scheme(url))
+}
+
+/// Opens a SQL database for migrations.
+pub fn connect(project: &Path, url: &str) -> Result<Box<dyn Database>> {
+    if let Some(db) = open_sql(project, url)? {
+        return Ok(db);
+    }
+
+    if is_mongo(url) {
+        return Err(Error::Validation(
+            "Migrations are not supported for MongoDB. Use sqlite: or postgres:// URLs.".into(),
+        ));
+    }
+
+    Err(unsupported_scheme(url))
+}
+
+fn open_sql(project: &Path, url: &str) -> Result<Option<Box<dyn Database>>> {
+    if let Some(path) = url
+        .strip_prefix("sqlite://")
+        .or_else(|| url.strip_prefix("sqlite:"))
+    {
+        let path = path.split('?').next().unwrap_or(path);
+        return open_sqlite(&project.join(path)).map(Some);
+    }
+
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        return connect_postgres(url).map(Some);
+    }
+
+    Ok(None)
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &Path) -> Result<Box<dyn Database>> {
+    Ok(Box::new(sqlite::Sqlite::open(path)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_path: &Path) -> Result<Box<dyn Database>> {
+    Err(Error::feature_disabled("SQLite", "sqlite"))
+}
+
+#[cfg(feature = "postgres")]
+fn connect_postgres(url: &str) -> Result<Box<dyn Database>> {
+    Ok(Box::new(postgres::Postgres::connect(url)?))
+}
+
+#[cfg(not(feature = "postgres"))]
+fn connect_postgres(_url: &str) -> Result<Box<dyn Database>> {
+    Err(Error::feature_disabled("PostgreSQL", "postgres"))
+}
+
+#[cfg(feature = "mongodb")]
+fn connect_mongo(project: &Path, url: &str) -> Result<Connection> {
+    let name = env_value(project, DATABASE_NAME_ENV)?;
+    Ok(Connection::Mongo(Mongo::connect(url, name.as_deref())?))
+}
+
+#[cfg(not(feature = "mongodb"))]
+fn connect_mongo(_project: &Path, _url: &str) -> Result<Connection> {
+    Err(Error::feature_disabled("MongoDB", "mongodb"))
+}
+
+fn unsupported_scheme(url: &str) -> Error {
+    let scheme = url.split(':').next().unwrap_or(url);
+    Error::Validation(format!(
+        "Unsupported database URL scheme '{}'. Expected sqlite:, postgres:// or mongodb://.",
+        scheme
+    ))
+}
+
+fn is_mongo(url: &str) -> bool {
+    url.starts_with("mongodb://") || url.starts_with("mongodb+srv://")
+}
+
+/// Reads `DATABASE_URL` from the environment, falling back to the project's `.env`.
+pub fn database_url(project: &Path) -> Result<String> {
+    env_value(project, DATABASE_URL_ENV)?.ok_or_else(|| {
+        Error::Validation(format!(
+            "{} is not set. Add it to .env or pass --database-url.",
+            DATABASE_URL_ENV
+        ))
+    })
+}
+
+/// Reads `key` from the environment, falling back to the project's `.env`.
+///
+/// Empty values count as unset.
+pub fn env_value(project: &Path, key: &str) -> Result<Option<String>> {
+    if let Some(value) = std::env::var(key).ok().filter(|v| !v.is_empty()) {
+        return Ok(Some(value));
+    }
+
+    let env_file = project.join(".env");
+    if !env_file.exists() {
+        return Ok(None);
+    }
+
+    let entries = dotenvy::from_path_iter(&env_file)
+        .map_err(|e| Error::Validation(format!("Failed to read {}: {}", env_file.display(), e)))?;
+    for entry in entries {
+        let (name, value) = entry.map_err(|e| {
+            Error::Validation(format!("Failed to parse {}: {}", env_file.display(), e))
+        })?;
+        if name == key && !value.is_empty() {
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(None)
+}
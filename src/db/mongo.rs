@@ -0,0 +1,146 @@
+use super::{INDEXES, IndexSpec, USER_FIELDS};
+use crate::error::{Error, Result};
+use mongodb::IndexModel;
+use mongodb::bson::{Document, doc};
+use mongodb::error::ErrorKind;
+use mongodb::options::IndexOptions;
+use mongodb::sync::{Client, Database};
+
+/// [`user_schema`](super::user_schema) as BSON, built from the same [`USER_FIELDS`].
+pub fn user_validator() -> Document {
+    let mut properties = Document::new();
+    for spec in USER_FIELDS {
+        let mut property = doc! { "bsonType": spec.bson_type };
+        if let Some(items) = spec.items {
+            property.insert("items", doc! { "bsonType": items });
+        }
+        if let Some(description) = spec.description {
+            property.insert("description", description);
+        }
+        properties.insert(spec.key, property);
+    }
+    let required: Vec<&str> = USER_FIELDS
+        .iter()
+        .filter(|spec| spec.required)
+        .map(|spec| spec.key)
+        .collect();
+
+    doc! { "bsonType": "object", "required": required, "properties": properties }
+}
+
+const NAMESPACE_NOT_FOUND: i32 = 26;
+
+pub struct Mongo {
+    database: Database,
+}
+
+impl Mongo {
+    /// Connects to `url`, using `name` or else the database in the URL path.
+    pub fn connect(url: &str, name: Option<&str>) -> Result<Self> {
+        log::debug!("Connecting to MongoDB");
+        let client = Client::with_uri_str(url).map_err(database_error)?;
+        let database = match name {
+            Some(name) => client.database(name),
+            None => client.default_database().ok_or_else(|| {
+                Error::Validation(
+                    "No MongoDB database name. Set DATABASE in .env or add it to the URL path."
+                        .into(),
+                )
+            })?,
+        };
+        Ok(Self { database })
+    }
+
+    /// Inserts `documents` into `collection`, returning how many were inserted.
+    pub fn insert(&self, collection: &str, documents: Vec<Document>) -> Result<u64> {
+        if documents.is_empty() {
+            return Ok(0);
+        }
+
+        let result = self
+            .database
+            .collection::<Document>(collection)
+            .insert_many(documents)
+            .run()
+            .map_err(database_error)?;
+        Ok(result.inserted_ids.len() as u64)
+    }
+
+    /// Creates every index in [`INDEXES`] that is missing.
+    ///
+    /// Returns the specs with whether each was created. An existing index with
+    /// the same name but different keys or options is reported as an error.
+    pub fn sync_indexes(&self) -> Result<Vec<(IndexSpec, bool)>> {
+        let mut outcomes = Vec::new();
+        for spec in INDEXES {
+            let collection = self.database.collection::<Document>(spec.collection);
+            let existing = match collection.list_index_names().run() {
+                Ok(names) => names,
+                Err(e) if command_code(&e) == Some(NAMESPACE_NOT_FOUND) => Vec::new(),
+                Err(e) => return Err(database_error(e)),
+            };
+
+            let created = !existing.iter().any(|name| name == spec.name);
+            // Creating an identical index is a no-op, and also detects conflicting definitions.
+            let index = IndexModel::builder()
+                .keys(doc! { spec.field: 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name(spec.name.to_string())
+                        .unique(spec.unique)
+                        .build(),
+                )
+                .build();
+            collection
+                .create_index(index)
+                .run()
+                .map_err(|e| Error::Database(format!("{}: {}", spec.name, e)))?;
+
+            outcomes.push((*spec, created));
+        }
+        Ok(outcomes)
+    }
+
+    /// Installs [`user_validator`] as the validator of the `users` collection.
+    pub fn apply_user_validator(&self) -> Result<()> {
+        let exists = self
+            .database
+            .list_collection_names()
+            .run()
+            .map_err(database_error)?
+            .iter()
+            .any(|name| name == "users");
+        let command = if exists { "collMod" } else { "create" };
+
+        self.database
+            .run_command(doc! {
+                command: "users",
+                "validator": { "$jsonSchema": user_validator() },
+                "validationLevel": "moderate",
+            })
+            .run()
+            .map(|_| ())
+            .map_err(database_error)
+    }
+
+    /// Whether `collection` has a document matching `filter`.
+    pub fn exists(&self, collection: &str, filter: Document) -> Result<bool> {
+        self.database
+            .collection::<Document>(collection)
+            .find_one(filter)
+            .run()
+            .map(|found| found.is_some())
+            .map_err(database_error)
+    }
+}
+
+fn command_code(error: &mongodb::error::Error) -> Option<i32> {
+    match error.kind.as_ref() {
+        ErrorKind::Command(e) => Some(e.code),
+        _ => None,
+    }
+}
+
+fn database_error(error: mongodb::error::Error) -> Error {
+    Error::Database(error.to_string())
+}
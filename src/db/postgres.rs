@@ -0,0 +1,93 @@
+use crate::db::{AppliedMigration, Database, MIGRATIONS_TABLE};
+use crate::error::{Error, Result};
+use postgres::{Client, NoTls};
+
+pub struct Postgres {
+    client: Client,
+}
+
+impl Postgres {
+    pub fn connect(url: &str) -> Result<Self> {
+        log::debug!("Connecting to PostgreSQL");
+        let client = Client::connect(url, NoTls).map_err(database_error)?;
+        Ok(Self { client })
+    }
+}
+
+impl Database for Postgres {
+    fn ensure_migrations_table(&mut self) -> Result<()> {
+        self.client
+            .batch_execute(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    version BIGINT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+                )",
+                MIGRATIONS_TABLE
+            ))
+            .map_err(database_error)
+    }
+
+    fn applied_migrations(&mut self) -> Result<Vec<AppliedMigration>> {
+        let rows = self
+            .client
+            .query(
+                &format!(
+                    "SELECT version, name, applied_at::TEXT FROM {} ORDER BY version",
+                    MIGRATIONS_TABLE
+                ),
+                &[],
+            )
+            .map_err(database_error)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| AppliedMigration {
+                version: row.get(0),
+                name: row.get(1),
+                applied_at: row.get(2),
+            })
+            .collect())
+    }
+
+    fn apply(&mut self, version: i64, name: &str, sql: &str) -> Result<()> {
+        let mut transaction = self.client.transaction().map_err(database_error)?;
+        transaction.batch_execute(sql).map_err(database_error)?;
+        transaction
+            .execute(
+                &format!(
+                    "INSERT INTO {} (version, name) VALUES ($1, $2)",
+                    MIGRATIONS_TABLE
+                ),
+                &[&version, &name],
+            )
+            .map_err(database_error)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    fn revert(&mut self, version: i64, sql: &str) -> Result<()> {
+        let mut transaction = self.client.transaction().map_err(database_error)?;
+        transaction.batch_execute(sql).map_err(database_error)?;
+        transaction
+            .execute(
+                &format!("DELETE FROM {} WHERE version = $1", MIGRATIONS_TABLE),
+                &[&version],
+            )
+            .map_err(database_error)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    fn execute_script(&mut self, sql: &str) -> Result<()> {
+        let mut transaction = self.client.transaction().map_err(database_error)?;
+        transaction.batch_execute(sql).map_err(database_error)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    fn execute(&mut self, sql: &str) -> Result<u64> {
+        self.client.execute(sql, &[]).map_err(database_error)
+    }
+}
+
+fn database_error(error: postgres::Error) -> Error {
+    Error::Database(error.to_string())
+}
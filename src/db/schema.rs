@@ -0,0 +1,155 @@
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+/// An index the generated repositories rely on.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IndexSpec {
+    pub collection: &'static str,
+    pub name: &'static str,
+    pub field: &'static str,
+    pub unique: bool,
+}
+
+/// The indexes the mongodb template creates at ignition.
+pub const INDEXES: &[IndexSpec] = &[
+    IndexSpec {
+        collection: "users",
+        name: "users_email_unique",
+        field: "email",
+        unique: true,
+    },
+    IndexSpec {
+        collection: "users",
+        name: "users_username",
+        field: "username",
+        unique: false,
+    },
+    IndexSpec {
+        collection: "refresh_tokens",
+        name: "refresh_tokens_token_hash_unique",
+        field: "tokenHash",
+        unique: true,
+    },
+    IndexSpec {
+        collection: "refresh_tokens",
+        name: "refresh_tokens_family_id",
+        field: "familyId",
+        unique: false,
+    },
+    IndexSpec {
+        collection: "refresh_tokens",
+        name: "refresh_tokens_user_id",
+        field: "userId",
+        unique: false,
+    },
+];
+
+/// A field of the mongodb template's `UserDocument`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FieldSpec {
+    /// Key in the stored document.
+    pub key: &'static str,
+    /// Name of the struct field.
+    pub field: &'static str,
+    pub rust_type: &'static str,
+    /// Attributes of the struct field, besides the rename to `key`.
+    pub attributes: &'static [&'static str],
+    pub bson_type: &'static str,
+    /// BSON type of the elements, for arrays.
+    pub items: Option<&'static str>,
+    /// Whether every stored document has the field.
+    pub required: bool,
+    pub description: Option<&'static str>,
+}
+
+/// The fields of `UserDocument`, which render both the struct and the
+/// `$jsonSchema` validator `rocket-cli db indexes --validator` installs.
+pub const USER_FIELDS: &[FieldSpec] = &[
+    FieldSpec {
+        key: "_id",
+        field: "id",
+        rust_type: "ObjectId",
+        attributes: &["#[schemars(with = \"ObjectIdJson\")]"],
+        bson_type: "objectId",
+        items: None,
+        required: true,
+        description: None,
+    },
+    FieldSpec {
+        key: "username",
+        field: "username",
+        rust_type: "String",
+        attributes: &[],
+        bson_type: "string",
+        items: None,
+        required: true,
+        description: None,
+    },
+    FieldSpec {
+        key: "email",
+        field: "email",
+        rust_type: "String",
+        attributes: &[],
+        bson_type: "string",
+        items: None,
+        required: true,
+        description: None,
+    },
+    FieldSpec {
+        key: "password",
+        field: "password",
+        rust_type: "String",
+        attributes: &[],
+        bson_type: "string",
+        items: None,
+        required: true,
+        description: None,
+    },
+    FieldSpec {
+        key: "roles",
+        field: "roles",
+        rust_type: "Vec<String>",
+        attributes: &["#[serde(default = \"default_roles\")]"],
+        bson_type: "array",
+        items: Some("string"),
+        required: false,
+        description: Some("Optional: documents created before roles existed get the default."),
+    },
+    FieldSpec {
+        key: "createdAt",
+        field: "created_at",
+        rust_type: "DateTime<Utc>",
+        attributes: &[
+            "#[serde(with = \"bson::serde_helpers::chrono_datetime_as_bson_datetime\")]",
+            "#[schemars(with = \"DateTimeJson\")]",
+        ],
+        bson_type: "date",
+        items: None,
+        required: true,
+        description: None,
+    },
+];
+
+/// The `$jsonSchema` of [`USER_FIELDS`], rendered to `src/models/user_schema.json`.
+pub fn user_schema() -> Value {
+    let properties: Map<String, Value> = USER_FIELDS
+        .iter()
+        .map(|spec| {
+            let mut property = json!({ "bsonType": spec.bson_type });
+            if let Some(items) = spec.items {
+                property["items"] = json!({ "bsonType": items });
+            }
+            if let Some(description) = spec.description {
+                property["description"] = description.into();
+            }
+            (spec.key.to_string(), property)
+        })
+        .collect();
+    let required: Vec<&str> = USER_FIELDS
+        .iter()
+        .filter(|spec| spec.required)
+        .map(|spec| spec.key)
+        .collect();
+
+    json!({ "bsonType": "object", "required": required, "properties": properties })
+}
@@ -0,0 +1,392 @@
+use crate::db::Connection;
+#[cfg(feature = "mongodb")]
+use crate::db::Mongo;
+use crate::error::{Error, Result};
+#[cfg(feature = "argon2")]
+use argon2::password_hash::rand_core::OsRng;
+#[cfg(feature = "argon2")]
+use argon2::password_hash::{PasswordHasher, SaltString};
+#[cfg(feature = "argon2")]
+use argon2::{Algorithm, Argon2, Params, Version};
+#[cfg(feature = "mongodb")]
+use mongodb::bson::{self, Document, doc};
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, TableLike};
+
+/// Directory seed files are read from, relative to the project.
+pub const SEEDS_DIR: &str = "seeds";
+
+/// Table or collection the built-in admin seed writes to.
+pub const USERS_TABLE: &str = "users";
+
+/// Roles given to the seeded admin, in the order the generated code stores them.
+#[cfg(feature = "argon2")]
+const ADMIN_ROLES: &[&str] = &["user", "admin"];
+
+/// Rows grouped by table (or collection), in file order.
+type Records = Vec<(String, Vec<Map<String, Value>>)>;
+
+/// The contents of a seed file.
+#[derive(Debug)]
+pub enum Seed {
+    /// A `.sql` script, run as-is.
+    Sql(String),
+    /// A `.json` or `.toml` file mapping table or collection names to rows.
+    Records(Records),
+}
+
+/// The result of running one seed file.
+#[derive(Debug, Serialize)]
+pub struct SeedOutcome {
+    pub file: PathBuf,
+    /// Inserted rows, or `None` for SQL scripts.
+    pub rows: Option<u64>,
+}
+
+/// The admin account created by `db seed --admin`.
+#[derive(Debug, Clone)]
+pub struct Admin {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+}
+
+/// Lists the `.sql`, `.json` and `.toml` files in `dir`, ordered by file name.
+pub fn discover(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(dir, e))? {
+        let path = entry.map_err(|e| Error::io(dir, e))?.path();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("sql" | "json" | "toml") => files.push(path),
+            _ => log::debug!("Skipping {}", path.display()),
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Reads and parses a seed file.
+pub fn load(path: &Path) -> Result<Seed> {
+    let contents = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    let invalid = |message: String| {
+        Error::Validation(format!("Invalid seed file {}: {}", path.display(), message))
+    };
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("sql") => Ok(Seed::Sql(contents)),
+        Some("json") => {
+            let Tables(tables) =
+                serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+            let mut records = Records::new();
+            for (table, rows) in tables {
+                let rows = match rows {
+                    Value::Array(rows) => rows
+                        .into_iter()
+                        .map(|row| match row {
+                            Value::Object(row) => Ok(row),
+                            _ => Err(invalid(format!("rows of '{}' must be objects", table))),
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => return Err(invalid(format!("'{}' must be an array of rows", table))),
+                };
+                records.push((table, rows));
+            }
+            Ok(Seed::Records(records))
+        }
+        Some("toml") => {
+            let doc = contents
+                .parse::<DocumentMut>()
+                .map_err(|e| invalid(e.to_string()))?;
+            let mut records = Records::new();
+            for (table, item) in doc.iter() {
+                let rows = match item {
+                    Item::ArrayOfTables(rows) => rows.iter().map(|row| toml_table(row)).collect(),
+                    Item::Value(toml_edit::Value::Array(rows)) => rows
+                        .iter()
+                        .map(|row| match row.as_inline_table() {
+                            Some(row) => Ok(toml_table(row)),
+                            None => Err(invalid(format!("rows of '{}' must be tables", table))),
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => {
+                        return Err(invalid(format!(
+                            "'{}' must be an array of tables, e.g. [[{}]]",
+                            table, table
+                        )));
+                    }
+                };
+                records.push((table.to_string(), rows));
+            }
+            Ok(Seed::Records(records))
+        }
+        _ => Err(invalid("expected a .sql, .json or .toml file".into())),
+    }
+}
+
+/// The top-level entries of a JSON seed, in file order.
+///
+/// `serde_json::Map` sorts its keys, but tables are seeded in the order they are
+/// written so that rows can reference rows of the tables above them.
+struct Tables(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for Tables {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct TablesVisitor;
+
+        impl<'de> Visitor<'de> for TablesVisitor {
+            type Value = Tables;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object of table or collection names")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Tables, A::Error> {
+                let mut tables = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    tables.push(entry);
+                }
+                Ok(Tables(tables))
+            }
+        }
+
+        deserializer.deserialize_map(TablesVisitor)
+    }
+}
+
+fn toml_table(table: &dyn TableLike) -> Map<String, Value> {
+    table
+        .iter()
+        .filter_map(|(key, item)| Some((key.to_string(), toml_value(item.as_value()?))))
+        .collect()
+}
+
+fn toml_value(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+        toml_edit::Value::Array(items) => Value::Array(items.iter().map(toml_value).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(toml_table(table)),
+    }
+}
+
+/// Runs a seed file against the database.
+///
+/// SQL scripts and the rows of one file each run in a single transaction on
+/// SQL databases. MongoDB only accepts `.json` and `.toml` seeds.
+pub fn run(connection: &mut Connection, path: &Path) -> Result<SeedOutcome> {
+    log::info!("Seeding {}", path.display());
+    let rows = match (load(path)?, connection) {
+        (Seed::Sql(sql), Connection::Sql(db)) => {
+            db.execute_script(&sql)?;
+            None
+        }
+        #[cfg(feature = "mongodb")]
+        (Seed::Sql(_), Connection::Mongo(_)) => {
+            return Err(Error::Validation(format!(
+                "{} is a SQL seed, but the database is MongoDB. Use .json or .toml seeds.",
+                path.display()
+            )));
+        }
+        (Seed::Records(records), Connection::Sql(db)) => {
+            let mut script = String::new();
+            let mut count = 0;
+            for (table, rows) in &records {
+                for row in rows {
+                    script.push_str(&insert_statement(table, row)?);
+                    script.push_str(";\n");
+                    count += 1;
+                }
+            }
+            db.execute_script(&script)?;
+            Some(count)
+        }
+        #[cfg(feature = "mongodb")]
+        (Seed::Records(records), Connection::Mongo(mongo)) => {
+            let mut count = 0;
+            for (collection, rows) in records {
+                let documents = rows
+                    .into_iter()
+                    .map(Document::try_from)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|e| {
+                        Error::Validation(format!(
+                            "Invalid document for '{}' in {}: {}",
+                            collection,
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                count += mongo.insert(&collection, documents)?;
+            }
+            Some(count)
+        }
+    };
+
+    Ok(SeedOutcome {
+        file: path.to_path_buf(),
+        rows,
+    })
+}
+
+fn insert_statement(table: &str, row: &Map<String, Value>) -> Result<String> {
+    let columns = row
+        .keys()
+        .map(|column| identifier(column))
+        .collect::<Result<Vec<_>>>()?;
+    let values = row.values().map(sql_literal).collect::<Vec<_>>();
+
+    Ok(format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        identifier(table)?,
+        columns.join(", "),
+        values.join(", ")
+    ))
+}
+
+fn identifier(name: &str) -> Result<String> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        return Err(Error::Validation(format!(
+            "'{}' is not a valid table or column name.",
+            name
+        )));
+    }
+    Ok(format!("\"{}\"", name))
+}
+
+/// Renders a JSON value as a SQL literal; the database casts strings to the column type.
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".into(),
+        Value::Bool(true) => "TRUE".into(),
+        Value::Bool(false) => "FALSE".into(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote(s),
+        nested => quote(&nested.to_string()),
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Reads the `[default.auth.argon2]` cost from the project's Rocket.toml, as the
+/// generated `AuthConfig` does, falling back to the same defaults.
+#[cfg(feature = "argon2")]
+pub fn argon2_params(project: &Path) -> Result<Params> {
+    let rocket_toml = project.join("Rocket.toml");
+    let mut memory_kib = Params::DEFAULT_M_COST;
+    let mut iterations = Params::DEFAULT_T_COST;
+    let mut parallelism = Params::DEFAULT_P_COST;
+
+    if rocket_toml.exists() {
+        let contents = fs::read_to_string(&rocket_toml).map_err(|e| Error::io(&rocket_toml, e))?;
+        let doc = contents.parse::<DocumentMut>().map_err(|e| {
+            Error::Validation(format!("Failed to parse {}: {}", rocket_toml.display(), e))
+        })?;
+
+        if let Some(argon2) = doc
+            .get("default")
+            .and_then(|d| d.get("auth"))
+            .and_then(|a| a.get("argon2"))
+        {
+            for (key, target) in [
+                ("memory_kib", &mut memory_kib),
+                ("iterations", &mut iterations),
+                ("parallelism", &mut parallelism),
+            ] {
+                if let Some(value) = argon2.get(key).and_then(|v| v.as_integer()) {
+                    *target = u32::try_from(value).map_err(|_| {
+                        Error::Validation(format!("auth.argon2.{} is out of range", key))
+                    })?;
+                }
+            }
+        }
+    }
+
+    Params::new(memory_kib, iterations, parallelism, None)
+        .map_err(|e| Error::Validation(format!("Invalid auth.argon2 settings: {}", e)))
+}
+
+/// Hashes `password` with Argon2id, matching the generated `hash_password`.
+#[cfg(feature = "argon2")]
+pub fn hash_password(password: &str, params: Params) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| Error::Validation(format!("Failed to hash the admin password: {}", e)))
+}
+
+/// Creates the admin user unless one with the same email exists.
+///
+/// Returns whether a user was created.
+#[cfg(feature = "argon2")]
+pub fn seed_admin(connection: &mut Connection, admin: &Admin, params: Params) -> Result<bool> {
+    if admin.password.is_empty() {
+        return Err(Error::Validation(
+            "The admin password must not be empty.".into(),
+        ));
+    }
+
+    log::info!("Seeding admin user {}", admin.email);
+    let hash = hash_password(&admin.password, params)?;
+
+    match connection {
+        Connection::Sql(db) => {
+            let rows = db.execute(&format!(
+                "INSERT INTO {table} (username, email, password, roles) \
+                 SELECT {username}, {email}, {hash}, {roles} \
+                 WHERE NOT EXISTS (SELECT 1 FROM {table} WHERE email = {email})",
+                table = USERS_TABLE,
+                username = quote(&admin.username),
+                email = quote(&admin.email),
+                hash = quote(&hash),
+                roles = quote(&ADMIN_ROLES.join(",")),
+            ))?;
+            Ok(rows > 0)
+        }
+        #[cfg(feature = "mongodb")]
+        Connection::Mongo(mongo) => seed_mongo_admin(mongo, admin, hash),
+    }
+}
+
+#[cfg(feature = "mongodb")]
+fn seed_mongo_admin(mongo: &Mongo, admin: &Admin, hash: String) -> Result<bool> {
+    if mongo.exists(USERS_TABLE, doc! { "email": &admin.email })? {
+        return Ok(false);
+    }
+
+    mongo.insert(
+        USERS_TABLE,
+        vec![doc! {
+            "username": &admin.username,
+            "email": &admin.email,
+            "password": hash,
+            "roles": ADMIN_ROLES,
+            "createdAt": bson::DateTime::now(),
+        }],
+    )?;
+    Ok(true)
+}
@@ -0,0 +1,98 @@
+use crate::db::{AppliedMigration, Database, MIGRATIONS_TABLE};
+use crate::error::{Error, Result};
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+pub struct Sqlite {
+    connection: Connection,
+}
+
+impl Sqlite {
+    pub fn open(path: &Path) -> Result<Self> {
+        log::debug!("Opening SQLite database {}", path.display());
+        let connection = Connection::open(path).map_err(database_error)?;
+        Ok(Self { connection })
+    }
+}
+
+impl Database for Sqlite {
+    fn ensure_migrations_table(&mut self) -> Result<()> {
+        self.connection
+            .execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    version INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+                MIGRATIONS_TABLE
+            ))
+            .map_err(database_error)
+    }
+
+    fn applied_migrations(&mut self) -> Result<Vec<AppliedMigration>> {
+        let mut statement = self
+            .connection
+            .prepare(&format!(
+                "SELECT version, name, applied_at FROM {} ORDER BY version",
+                MIGRATIONS_TABLE
+            ))
+            .map_err(database_error)?;
+
+        let rows = statement
+            .query_map([], |row| {
+                Ok(AppliedMigration {
+                    version: row.get(0)?,
+                    name: row.get(1)?,
+                    applied_at: row.get(2)?,
+                })
+            })
+            .map_err(database_error)?;
+
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(database_error)
+    }
+
+    fn apply(&mut self, version: i64, name: &str, sql: &str) -> Result<()> {
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        transaction.execute_batch(sql).map_err(database_error)?;
+        transaction
+            .execute(
+                &format!(
+                    "INSERT INTO {} (version, name) VALUES (?1, ?2)",
+                    MIGRATIONS_TABLE
+                ),
+                params![version, name],
+            )
+            .map_err(database_error)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    fn revert(&mut self, version: i64, sql: &str) -> Result<()> {
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        transaction.execute_batch(sql).map_err(database_error)?;
+        transaction
+            .execute(
+                &format!("DELETE FROM {} WHERE version = ?1", MIGRATIONS_TABLE),
+                params![version],
+            )
+            .map_err(database_error)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    fn execute_script(&mut self, sql: &str) -> Result<()> {
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        transaction.execute_batch(sql).map_err(database_error)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    fn execute(&mut self, sql: &str) -> Result<u64> {
+        self.connection
+            .execute(sql, [])
+            .map(|rows| rows as u64)
+            .map_err(database_error)
+    }
+}
+
+fn database_error(error: rusqlite::Error) -> Error {
+    Error::Database(error.to_string())
+}
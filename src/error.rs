@@ -0,0 +1,142 @@
+use handlebars::{RenderError, RenderErrorReason};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned by every `rocket-cli` command handler.
+#[derive(Debug)]
+pub enum Error {
+    /// The requested template does not exist.
+    TemplateNotFound(String),
+
+    /// A template file could not be rendered.
+    Render {
+        file: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+
+    /// A filesystem operation failed.
+    Io { path: PathBuf, source: io::Error },
+
+    /// A git invocation failed.
+    Git(String),
+
+    /// A cargo invocation failed.
+    Cargo(String),
+
+    /// A TLS certificate could not be generated.
+    Certificate(String),
+
+    /// A database operation failed.
+    Database(String),
+
+    /// The user supplied invalid input.
+    Validation(String),
+}
+
+impl Error {
+    /// Process exit code reported for this error.
+    ///
+    /// `2` mirrors clap's usage-error code so invalid input looks the same
+    /// whether clap or a command handler rejected it.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Validation(_) => 2,
+            Error::TemplateNotFound(_) => 3,
+            Error::Render { .. } => 4,
+            Error::Io { .. } => 5,
+            Error::Git(_) => 6,
+            Error::Cargo(_) => 7,
+            Error::Certificate(_) => 8,
+            Error::Database(_) => 9,
+        }
+    }
+
+    /// Stable, machine-readable identifier used in JSON output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::TemplateNotFound(_) => "template_not_found",
+            Error::Render { .. } => "render_error",
+            Error::Io { .. } => "io_error",
+            Error::Git(_) => "git_error",
+            Error::Cargo(_) => "cargo_error",
+            Error::Certificate(_) => "certificate_error",
+            Error::Database(_) => "database_error",
+            Error::Validation(_) => "validation_error",
+        }
+    }
+
+    /// Input that needs a cargo feature this build was compiled without.
+    pub fn feature_disabled(what: &str, feature: &str) -> Self {
+        Error::Validation(format!(
+            "{} support is not compiled into this rocket-cli. Reinstall it with `--features {}`.",
+            what, feature
+        ))
+    }
+
+    pub(crate) fn io(path: &Path, source: io::Error) -> Self {
+        Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub(crate) fn render(file: &Path, error: RenderError) -> Self {
+        let line = error.line_no.or_else(|| match error.reason() {
+            RenderErrorReason::TemplateError(e) => e.pos().map(|(line, _)| line),
+            _ => None,
+        });
+
+        Error::Render {
+            file: file.to_path_buf(),
+            line,
+            message: error.reason().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TemplateNotFound(name) => write!(
+                f,
+                "Rocket template '{}' not found. Use `rocket-cli new --list` to see available templates.",
+                name
+            ),
+            Error::Render {
+                file,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "Failed to render '{}' (line {}): {}",
+                file.display(),
+                line,
+                message
+            ),
+            Error::Render {
+                file,
+                line: None,
+                message,
+            } => write!(f, "Failed to render '{}': {}", file.display(), message),
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Git(message) => write!(f, "Git error: {}", message),
+            Error::Cargo(message) => write!(f, "Cargo error: {}", message),
+            Error::Certificate(message) => write!(f, "Certificate error: {}", message),
+            Error::Database(message) => write!(f, "Database error: {}", message),
+            Error::Validation(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
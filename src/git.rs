@@ -0,0 +1,205 @@
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::{Command, Output};
+
+pub const DEFAULT_COMMIT_MESSAGE: &str = "Initial commit (generated by rocket-cli)";
+
+/// Options for `rocket-cli new --git`.
+#[derive(Debug, Clone)]
+pub struct GitOptions {
+    /// Initial branch name; git's own default is used when unset.
+    pub branch: Option<String>,
+    /// Commit author in `Name <email>` form; git's configured identity is used when unset.
+    pub author: Option<String>,
+    pub message: String,
+}
+
+impl Default for GitOptions {
+    fn default() -> Self {
+        Self {
+            branch: None,
+            author: None,
+            message: DEFAULT_COMMIT_MESSAGE.into(),
+        }
+    }
+}
+
+impl GitOptions {
+    /// Rejects a malformed author or branch before any files are written.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(author) = &self.author {
+            parse_author(author)?;
+        }
+
+        if let Some(branch) = &self.branch {
+            validate_branch(branch)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What `init_repository` ended up doing.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GitOutcome {
+    /// A repository was created and the generated files committed.
+    Committed { branch: String, commit: String },
+    /// A repository was created but no commit could be made.
+    Initialized { reason: String },
+    /// Git was not run at all.
+    Skipped { reason: String },
+}
+
+/// Initializes a repository in `dir` and commits its contents.
+///
+/// A missing `git` binary or a `dir` that already lives inside a work tree
+/// are not errors: the step is skipped with a warning instead.
+pub fn init_repository(dir: &Path, options: &GitOptions) -> Result<GitOutcome> {
+    options.validate()?;
+    let author = options.author.as_deref().map(parse_author).transpose()?;
+
+    match Command::new("git").arg("--version").output() {
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let reason = "git executable not found".to_string();
+            log::warn!("{}; skipping repository initialization", reason);
+            return Ok(GitOutcome::Skipped { reason });
+        }
+        Err(e) => return Err(Error::Git(format!("failed to run git: {}", e))),
+    }
+
+    let parent = dir
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if is_inside_work_tree(parent) {
+        let reason = format!("{} is already inside a git work tree", dir.display());
+        log::warn!("{}; skipping repository initialization", reason);
+        return Ok(GitOutcome::Skipped { reason });
+    }
+
+    log::info!("Initializing git repository in {}", dir.display());
+    run(dir, &["init", "--quiet"])?;
+
+    if let Some(branch) = &options.branch {
+        log::debug!("Setting initial branch to '{}'", branch);
+        run(
+            dir,
+            &["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)],
+        )?;
+    }
+
+    let mut identity = Vec::new();
+    if let Some((name, email)) = &author {
+        identity.push(format!("user.name={}", name));
+        identity.push(format!("user.email={}", email));
+    } else if !has_identity(dir)? {
+        let reason =
+            "no git identity configured (set user.name and user.email or pass --git-author)"
+                .to_string();
+        log::warn!("{}; skipping initial commit", reason);
+        return Ok(GitOutcome::Initialized { reason });
+    }
+
+    run(dir, &["add", "--all"])?;
+
+    let mut commit = Vec::new();
+    for pair in &identity {
+        commit.extend(["-c", pair.as_str()]);
+    }
+    commit.extend(["commit", "--quiet", "-m", options.message.as_str()]);
+
+    log::info!("Creating initial commit");
+    run(dir, &commit)?;
+
+    Ok(GitOutcome::Committed {
+        branch: stdout(dir, &["rev-parse", "--abbrev-ref", "HEAD"])?,
+        commit: stdout(dir, &["rev-parse", "--short", "HEAD"])?,
+    })
+}
+
+/// Splits `Name <email>` into its parts.
+fn parse_author(author: &str) -> Result<(String, String)> {
+    let invalid = || {
+        Error::Validation(format!(
+            "Invalid git author '{}'. Expected the form `Name <email>`.",
+            author
+        ))
+    };
+
+    let (name, rest) = author.split_once('<').ok_or_else(invalid)?;
+    let email = rest.strip_suffix('>').ok_or_else(invalid)?.trim();
+    let name = name.trim();
+
+    if name.is_empty() || email.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok((name.to_string(), email.to_string()))
+}
+
+fn validate_branch(branch: &str) -> Result<()> {
+    let valid = Command::new("git")
+        .args(["check-ref-format", "--branch", branch])
+        .output()
+        .map(|o| o.status.success())
+        // Without git the branch is never used, so there is nothing to reject.
+        .unwrap_or(true);
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "'{}' is not a valid git branch name.",
+            branch
+        )))
+    }
+}
+
+fn is_inside_work_tree(dir: &Path) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .map(|o| o.status.success() && o.stdout.starts_with(b"true"))
+        .unwrap_or(false)
+}
+
+/// Whether both `user.name` and `user.email` are configured; `git commit` needs each.
+fn has_identity(dir: &Path) -> Result<bool> {
+    Ok(!stdout(dir, &["config", "user.name"])?.is_empty()
+        && !stdout(dir, &["config", "user.email"])?.is_empty())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<Output> {
+    log::debug!("Running git {}", args.join(" "));
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| Error::Git(format!("failed to run `git {}`: {}", args.join(" "), e)))
+}
+
+fn run(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = git(dir, args)?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::Git(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+fn stdout(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = git(dir, args)?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -1,2 +1,10 @@
+pub mod cert;
 pub mod commands;
-pub mod templates;
\ No newline at end of file
+pub mod db;
+pub mod error;
+pub mod git;
+pub mod logger;
+pub mod output;
+pub mod prompt;
+pub mod scaffold;
+pub mod templates;
@@ -0,0 +1,52 @@
+use colored::*;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Minimal stderr logger for `rocket-cli`'s own log records.
+///
+/// Info records are printed as plain status lines so the default output
+/// reads like regular CLI progress; other levels carry a colored prefix.
+struct Logger {
+    level: LevelFilter,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level && metadata.target().starts_with("rocket_cli")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match record.level() {
+            Level::Error => eprintln!("{} {}", "error:".red().bold(), record.args()),
+            Level::Warn => eprintln!("{} {}", "warning:".yellow().bold(), record.args()),
+            Level::Info => eprintln!("{}", record.args()),
+            Level::Debug => eprintln!("{} {}", "debug:".blue(), record.args()),
+            Level::Trace => eprintln!("{} {}", "trace:".dimmed(), record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Maps the `-v`/`-q` flags to a log level.
+pub fn level_for(verbose: u8, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::Error;
+    }
+
+    match verbose {
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Installs the logger. Calling it more than once is a no-op.
+pub fn init(level: LevelFilter) {
+    if log::set_boxed_logger(Box::new(Logger { level })).is_ok() {
+        log::set_max_level(level);
+    }
+}
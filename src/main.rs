@@ -1,10 +1,9 @@
 use clap::Parser;
 use colored::*;
-use commands::Command;
-use is_terminal::*;
-
-mod commands;
-mod templates;
+use rocket_cli::commands::{self, Command};
+use rocket_cli::logger;
+use rocket_cli::output::{self, ColorChoice, Format};
+use serde_json::json;
 
 #[derive(Parser)]
 #[command(name = "rocket-cli")]
@@ -15,19 +14,59 @@ mod templates;
 struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = Format::Human)]
+    format: Format,
+
+    /// Increase log verbosity (-v: debug, -vv: trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Silence progress output; only results and errors are printed
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// When to use colors (honors NO_COLOR in `auto` mode)
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    match cli.command {
-        Some(cmd) => commands::handle_command(cmd),
-        None => default_message(),
+    cli.color.apply();
+    logger::init(logger::level_for(cli.verbose, cli.quiet));
+
+    let result = match cli.command {
+        Some(cmd) => commands::handle_command(cmd, cli.format),
+        None => {
+            default_message(cli.format);
+            Ok(())
+        }
+    };
+
+    if let Err(error) = result {
+        output::emit_error(cli.format, &error);
+        std::process::exit(error.exit_code());
     }
 }
 
-fn default_message() {
-    let is_tty = std::io::stdout().is_terminal();
+fn default_message(format: Format) {
+    if format == Format::Json {
+        println!(
+            "{}",
+            json!({
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+                "docs": "https://rocket.rs",
+                "repository": "https://github.com/irfanghat/rocket-cli",
+            })
+        );
+        return;
+    }
+
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
 
     let rocket_art = r#"
          .
@@ -40,23 +79,20 @@ fn default_message() {
     |_|     |_|     incredible usability, productivity & performance.
     "#;
 
-    if is_tty {
+    if colorize {
         println!("{}", rocket_art.bright_red());
         println!("{}", "Usage Examples:".bold());
         println!(
-            "  {}      {}",
-            "rocket-cli new my-api".cyan(),
-            "Scaffold a new Rocket project"
+            "  {}      Scaffold a new Rocket project",
+            "rocket-cli new my-api".cyan()
         );
         println!(
-            "  {}  {}",
-            "rocket-cli new --list".cyan(),
-            "List available templates"
+            "  {}  List available templates",
+            "rocket-cli new --list".cyan()
         );
         println!(
-            "  {}         {}",
-            "rocket-cli run".cyan(),
-            "Run your Rocket application"
+            "  {}         Run your Rocket application",
+            "rocket-cli run".cyan()
         );
 
         println!();
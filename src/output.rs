@@ -0,0 +1,82 @@
+use crate::error::Error;
+use clap::ValueEnum;
+use colored::*;
+use is_terminal::IsTerminal;
+use serde::Serialize;
+use serde_json::json;
+
+/// Output format selected with the global `--format` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Colored, human-readable text
+    #[default]
+    Human,
+    /// A single JSON document on stdout
+    Json,
+}
+
+/// When to emit ANSI colors, selected with the global `--color` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output should be colored under this choice.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && std::io::stdout().is_terminal()
+            }
+        }
+    }
+
+    /// Applies the choice globally so every `colored` string honors it.
+    pub fn apply(self) {
+        colored::control::set_override(self.enabled());
+    }
+}
+
+/// The result of a command, printable either as text or as JSON.
+pub trait Report: Serialize {
+    fn print_human(&self);
+}
+
+/// Prints a command report in the requested format.
+pub fn emit<R: Report>(format: Format, report: &R) {
+    match format {
+        Format::Human => report.print_human(),
+        Format::Json => print_json(report),
+    }
+}
+
+/// Prints an error in the requested format.
+///
+/// JSON errors go to stdout alongside regular reports so scripts only have
+/// to parse a single stream.
+pub fn emit_error(format: Format, error: &Error) {
+    match format {
+        Format::Human => eprintln!("{} {}", "error:".red().bold(), error.to_string().red()),
+        Format::Json => print_json(&json!({
+            "error": {
+                "code": error.code(),
+                "message": error.to_string(),
+                "exit_code": error.exit_code(),
+            }
+        })),
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Failed to serialize output: {}", e),
+    }
+}
@@ -0,0 +1,32 @@
+use crate::error::{Error, Result};
+use is_terminal::IsTerminal;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Whether questions can be asked: both stdin and stderr must be terminals.
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Asks a question on stderr and reads one line from stdin.
+///
+/// An empty answer (or end of input) yields `default`.
+pub fn ask(question: &str, default: &str) -> Result<String> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{} [{}]: ", question, default)
+        .and_then(|_| stderr.flush())
+        .map_err(|e| Error::io(Path::new("<stderr>"), e))?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| Error::io(Path::new("<stdin>"), e))?;
+
+    let answer = answer.trim();
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer.to_string()
+    })
+}
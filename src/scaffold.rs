@@ -0,0 +1,196 @@
+use crate::db::{USER_FIELDS, user_schema};
+use crate::error::{Error, Result};
+use crate::templates::{OPENAPI_EXPORT_ENV, load_template_files};
+use handlebars::{Handlebars, handlebars_helper};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Quotes and escapes a string for TOML, e.g. `{{{toml_string this}}}` inside `{{#each}}`.
+handlebars_helper!(toml_string: |value: str| toml_edit::Value::from(value).to_string());
+
+/// Context keys set by the scaffolder itself, which variables may not use.
+const RESERVED_VARS: &[&str] = &["template", "features", "cli"];
+
+/// Renders a Rocket project template in-process.
+///
+/// ```no_run
+/// use rocket_cli::scaffold::Scaffolder;
+///
+/// let project = Scaffolder::new("postgres")
+///     .var("project_name", "my-api")
+///     .render()?;
+///
+/// project.write_to("my-api")?;
+/// # Ok::<(), rocket_cli::error::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scaffolder {
+    template: String,
+    vars: BTreeMap<String, Value>,
+    features: BTreeSet<String>,
+}
+
+impl Scaffolder {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            vars: BTreeMap::new(),
+            features: BTreeSet::new(),
+        }
+    }
+
+    /// Sets a template variable, e.g. `project_name`.
+    ///
+    /// Lists and booleans reach templates as such, e.g. for `{{#each}}`.
+    /// `template`, `features` and `cli` are reserved; [`render`](Self::render) rejects them.
+    pub fn var(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Enables an optional template feature, exposed to templates as `features.<name>`.
+    pub fn feature(mut self, name: impl Into<String>) -> Self {
+        self.features.insert(name.into());
+        self
+    }
+
+    /// Renders every file of the template without touching the filesystem.
+    pub fn render(self) -> Result<RenderedProject> {
+        if let Some(name) = self
+            .vars
+            .keys()
+            .find(|name| RESERVED_VARS.contains(&name.as_str()))
+        {
+            return Err(Error::Validation(format!(
+                "'{}' is reserved and cannot be used as a template variable.",
+                name
+            )));
+        }
+
+        let template_files = load_template_files(&self.template)
+            .ok_or_else(|| Error::TemplateNotFound(self.template.clone()))?;
+        log::debug!(
+            "Resolved template '{}' ({} files)",
+            self.template,
+            template_files.len()
+        );
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("toml_string", Box::new(toml_string));
+        let ctx = self.context();
+        log::trace!("Template context: {}", ctx);
+
+        let mut files = Vec::with_capacity(template_files.len());
+        for (path, content) in template_files {
+            log::debug!("Rendering {}", path.display());
+            let contents = handlebars
+                .render_template(content, &ctx)
+                .map_err(|e| Error::render(&path, e))?;
+
+            // Files wrapped in a feature conditional render to nothing when it is off.
+            if contents.trim().is_empty() {
+                log::debug!("Skipping {} (empty)", path.display());
+                continue;
+            }
+            files.push(RenderedFile { path, contents });
+        }
+
+        Ok(RenderedProject {
+            template: self.template,
+            vars: self.vars,
+            features: self.features,
+            files,
+        })
+    }
+
+    fn context(&self) -> Value {
+        let mut ctx: Map<String, Value> = self
+            .vars
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let features = self
+            .features
+            .iter()
+            .map(|name| (name.clone(), Value::Bool(true)))
+            .collect();
+
+        ctx.insert("template".into(), Value::String(self.template.clone()));
+        ctx.insert("features".into(), Value::Object(features));
+        // Names the CLI and generated code must agree on.
+        ctx.insert(
+            "cli".into(),
+            json!({
+                "openapi_export_env": OPENAPI_EXPORT_ENV,
+                "user_fields": USER_FIELDS,
+                "user_schema": serde_json::to_string_pretty(&user_schema())
+                    .expect("the schema is valid JSON"),
+            }),
+        );
+
+        Value::Object(ctx)
+    }
+}
+
+/// A single rendered file, relative to the project root.
+#[derive(Debug, Clone)]
+pub struct RenderedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// The output of [`Scaffolder::render`].
+#[derive(Debug, Clone)]
+pub struct RenderedProject {
+    template: String,
+    vars: BTreeMap<String, Value>,
+    features: BTreeSet<String>,
+    files: Vec<RenderedFile>,
+}
+
+impl RenderedProject {
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn vars(&self) -> &BTreeMap<String, Value> {
+        &self.vars
+    }
+
+    pub fn features(&self) -> &BTreeSet<String> {
+        &self.features
+    }
+
+    pub fn files(&self) -> &[RenderedFile] {
+        &self.files
+    }
+
+    /// Looks up the rendered contents of a file by its relative path.
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|f| f.path == path.as_ref())
+            .map(|f| f.contents.as_str())
+    }
+
+    /// Writes every rendered file below `root`, creating directories as needed.
+    pub fn write_to(&self, root: impl AsRef<Path>) -> Result<()> {
+        let root = root.as_ref();
+
+        for file in &self.files {
+            let full_path = root.join(&file.path);
+            log::debug!("Writing {}", full_path.display());
+
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+            }
+
+            fs::write(&full_path, &file.contents).map_err(|e| Error::io(&full_path, e))?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,56 @@
 pub const CORS: &str = r#"use rocket::fairing::{Fairing, Info, Kind};
-use rocket::http::Header;
+use rocket::http::{Header, Method, Status};
+use rocket::serde::Deserialize;
 use rocket::{Request, Response};
 
+use std::io::Cursor;
+
+/// CORS settings from the `[default.cors]` section of Rocket.toml.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct CorsConfig {
+    /// Exact origins to allow, or `"*"` for any origin.
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// How long (in seconds) browsers may cache a preflight response.
+    pub max_age: u32,
+    pub allow_credentials: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]
+                .map(String::from)
+                .to_vec(),
+            allowed_headers: ["Content-Type", "Authorization"].map(String::from).to_vec(),
+            max_age: 86400,
+            allow_credentials: false,
+        }
+    }
+}
+
+impl CorsConfig {
+    fn allows(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == origin)
+    }
+
+    /// Browsers reject credentialed responses that allow any origin.
+    pub fn allows_any_origin_with_credentials(&self) -> bool {
+        self.allow_credentials && self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+/// Adds CORS headers for allowed origins and answers preflight requests.
+///
+/// The [`CorsConfig`] is put into managed state by `AppConfig::fairing()`.
+///
+/// Preflight (`OPTIONS` with `Access-Control-Request-Method`) requests have
+/// no matching route, so their 404 is turned into a `204 No Content` here.
 pub struct Cors;
 
 #[rocket::async_trait]
@@ -13,26 +62,170 @@ impl Fairing for Cors {
         }
     }
 
-    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(config) = request.rocket().state::<CorsConfig>() else {
+            return;
+        };
+        let Some(origin) = request.headers().get_one("Origin") else {
+            return;
+        };
+
+        response.adjoin_header(Header::new("Vary", "Origin"));
+        if !config.allows(origin) {
+            return;
+        }
+
         response.set_header(Header::new(
-            "Access-Control-Allow-Methods",
-            "POST, PATCH, PUT, DELETE, HEAD, OPTIONS, GET",
+            "Access-Control-Allow-Origin",
+            origin.to_string(),
         ));
-        response.set_header(Header::new("Access-Control-Allow-Headers", "*"));
-        response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        if config.allow_credentials {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+
+        let is_preflight = request.method() == Method::Options
+            && request.headers().contains("Access-Control-Request-Method");
+        if is_preflight {
+            response.set_status(Status::NoContent);
+            response.set_sized_body(0, Cursor::new(""));
+            response.remove_header("Content-Type");
+            response.set_header(Header::new(
+                "Access-Control-Allow-Methods",
+                config.allowed_methods.join(", "),
+            ));
+            response.set_header(Header::new(
+                "Access-Control-Allow-Headers",
+                config.allowed_headers.join(", "),
+            ));
+            response.set_header(Header::new(
+                "Access-Control-Max-Age",
+                config.max_age.to_string(),
+            ));
+        }
     }
 }
+{{#unless (eq template "minimal")}}
+
+/// Header carrying the request id, accepted from clients and proxies and echoed back.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+struct CachedRequestId(String);
+
+/// The id of the current request: the incoming `X-Request-Id` when it is a
+/// short plain token, otherwise a random one.
+pub fn request_id<'r>(request: &'r Request<'_>) -> &'r str {
+    let cached = request.local_cache(|| {
+        let id = request
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .filter(|id| {
+                (1..=64).contains(&id.len())
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            })
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{:032x}", rand::random::<u128>()));
+        CachedRequestId(id)
+    });
+    &cached.0
+}
+
+/// Sets the `X-Request-Id` response header, see [`request_id`].
+pub struct RequestId;
+
+#[rocket::async_trait]
+impl Fairing for RequestId {
+    fn info(&self) -> Info {
+        Info {
+            name: "Add request id to responses",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        response.set_header(Header::new(REQUEST_ID_HEADER, request_id(request).to_string()));
+    }
+}
+{{/unless}}
 "#;
 
-pub const AUTH_GUARD: &str = r#"use rocket::http::Status;
+pub const AUTH_GUARD: &str = r#"use chrono::{DateTime, Utc};
+use rocket::http::Status;
+use rocket::outcome::try_outcome;
 use rocket::request::{FromRequest, Outcome, Request};
 
-use crate::auth::validate_token;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::auth::{AuthConfig, validate_token};
+use crate::repositories::UserStore;
 
+/// Claims of a validated access token.
+///
+/// The token is read from an `Authorization: Bearer <jwt>` header, falling
+/// back to the `auth_token` cookie set by `/login`.
 #[allow(dead_code)]
 pub struct AuthClaims {
-    pub credentials: String,
+    pub subject: String,
+    pub session_id: String,
+    pub expires_at: DateTime<Utc>,
+    pub roles: Vec<String>,
+}
+
+impl AuthClaims {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    /// Ownership check: the subject is the resource owner (by email) or an admin.
+    pub fn is_self_or_admin(&self, owner_email: &str) -> bool {
+        self.subject == owner_email || self.has_role(Admin::NAME)
+    }
+}
+
+/// A role that can be required with [`RequireRole`].
+pub trait Role {
+    const NAME: &'static str;
+}
+
+pub struct Admin;
+
+impl Role for Admin {
+    const NAME: &'static str = "admin";
+}
+
+/// Guard that only succeeds for authenticated users holding role `R`,
+/// e.g. `_admin: RequireRole<Admin>`. Responds with 403 otherwise.
+#[allow(dead_code)]
+pub struct RequireRole<R: Role> {
+    pub claims: AuthClaims,
+    _role: PhantomData<R>,
+}
+
+#[rocket::async_trait]
+impl<'r, R: Role> FromRequest<'r> for RequireRole<R> {
+    type Error = AuthError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let claims = try_outcome!(req.guard::<AuthClaims>().await);
+
+        if claims.has_role(R::NAME) {
+            Outcome::Success(RequireRole {
+                claims,
+                _role: PhantomData,
+            })
+        } else {
+            Outcome::Error((Status::Forbidden, AuthError::Forbidden))
+        }
+    }
+}
+
+fn bearer_token<'a>(req: &'a Request<'_>) -> Option<&'a str> {
+    req.headers()
+        .get_one("Authorization")?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
 }
 
 #[rocket::async_trait]
@@ -40,16 +233,37 @@ impl<'r> FromRequest<'r> for AuthClaims {
     type Error = AuthError;
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        let cookie = req.cookies().get("auth_token");
+        let (config, repo) = match (
+            req.rocket().state::<AuthConfig>(),
+            req.rocket().state::<Arc<dyn UserStore>>(),
+        ) {
+            (Some(config), Some(repo)) => (config, repo),
+            _ => return Outcome::Error((Status::InternalServerError, AuthError::Misconfigured)),
+        };
 
-        match cookie {
-            Some(c) => match validate_token(c.value()).await {
-                Ok(credentials) => Outcome::Success(AuthClaims { credentials }),
-                Err(_) => {
-                    Outcome::Error((Status::Unauthorized, AuthError::InvalidToken(())))
-                }
+        let token = match bearer_token(req) {
+            Some(token) => token.to_string(),
+            None => match req.cookies().get("auth_token") {
+                Some(cookie) => cookie.value().to_string(),
+                None => return Outcome::Error((Status::Unauthorized, AuthError::MissingToken)),
             },
-            None => Outcome::Error((Status::Unauthorized, AuthError::MissingToken)),
+        };
+
+        let claims = match validate_token(&token, config) {
+            Ok(claims) => claims,
+            Err(_) => return Outcome::Error((Status::Unauthorized, AuthError::InvalidToken(()))),
+        };
+
+        // Access tokens stay valid only while their session has not been revoked.
+        match repo.is_session_active(&claims.sid).await {
+            Ok(true) => Outcome::Success(AuthClaims {
+                expires_at: DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_default(),
+                subject: claims.sub,
+                session_id: claims.sid,
+                roles: claims.roles,
+            }),
+            Ok(false) => Outcome::Error((Status::Unauthorized, AuthError::Revoked)),
+            Err(_) => Outcome::Error((Status::InternalServerError, AuthError::Misconfigured)),
         }
     }
 }
@@ -58,96 +272,503 @@ impl<'r> FromRequest<'r> for AuthClaims {
 pub enum AuthError {
     MissingToken,
     InvalidToken(()),
+    Revoked,
+    // Only produced by `RequireRole`, which may not be used by every route set.
+    #[allow(dead_code)]
+    Forbidden,
+    Misconfigured,
+}
+"#;
+
+pub const CONFIG: &str = r#"use rocket::fairing::AdHoc;
+{{#unless (eq template "minimal")}}
+use rocket::figment::providers::Env;
+{{/unless}}
+use rocket::figment::Figment;
+use rocket::serde::Deserialize;
+
+{{#unless (eq template "minimal")}}
+use crate::auth::AuthConfig;
+{{/unless}}
+use crate::fairings::CorsConfig;
+
+/// Application settings, extracted from Rocket's figment at ignition.
+///
+/// Values come from the `[default.*]` sections of Rocket.toml, `ROCKET_*`
+/// environment variables and, for secrets, the environment or `.env`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct AppConfig {
+{{#unless (eq template "minimal")}}
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub features: Features,
+{{/unless}}
+    pub cors: CorsConfig,
+}
+{{#unless (eq template "minimal")}}
+
+/// Database connection settings; `url` is read from `DATABASE_URL`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct DatabaseConfig {
+    pub url: String,
+{{#if (eq template "mongodb")}}
+    /// Database name, read from `DATABASE`.
+    pub name: String,
+    /// Enforce the `UserDocument` `$jsonSchema` on the users collection.
+    pub validate_schema: bool,
+{{/if}}
+    /// Maximum number of pooled connections.
+    pub max_connections: u32,
+    /// Timeout for opening (or acquiring) a connection.
+    pub connect_timeout_secs: u64,
+    /// How long to keep retrying an unreachable database at startup.
+    pub retry_for_secs: u64,
+{{#if features.migrations}}
+    /// Apply pending embedded migrations once connected.
+    pub run_migrations: bool,
+{{/if}}
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+{{#if (eq template "mongodb")}}
+            name: String::new(),
+            validate_schema: false,
+{{/if}}
+            max_connections: 10,
+            connect_timeout_secs: 5,
+            retry_for_secs: 30,
+{{#if features.migrations}}
+            run_migrations: true,
+{{/if}}
+        }
+    }
+}
+
+/// Optional behavior toggled from `[default.features]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct Features {
+    /// Whether `POST /register` accepts new accounts.
+    pub registration: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self { registration: true }
+    }
+}
+{{/unless}}
+
+/// Rocket's default figment{{#unless (eq template "minimal")}}, plus the secrets kept in the environment or `.env`{{/unless}}.
+pub fn figment() -> Figment {
+{{#unless (eq template "minimal")}}
+    dotenvy::dotenv().ok();
+
+    let figment = rocket::Config::figment();
+    let secret_env = figment
+        .extract_inner::<String>("auth.secret_env")
+        .unwrap_or_else(|_| AuthConfig::default().secret_env);
+
+    figment
+        .merge(env_var("DATABASE_URL", "database.url"))
+{{#if (eq template "mongodb")}}
+        .merge(env_var("DATABASE", "database.name"))
+{{/if}}
+        .merge(env_var(&secret_env, "auth.secret"))
+{{else}}
+    rocket::Config::figment()
+{{/unless}}
+}
+{{#unless (eq template "minimal")}}
+
+/// Maps a single environment variable onto a config key.
+fn env_var(name: &str, key: &'static str) -> Env {
+    Env::raw().only(&[name]).map(move |_| key.into()).global()
+}
+{{/unless}}
+
+impl AppConfig {
+    /// Extracts and validates the config, aborting ignition with every problem found.
+    ///
+    /// Also puts the sections other fairings and guards need into managed state.
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Load application config", |rocket| async {
+            let {{#unless (eq template "minimal")}}mut {{/unless}}config = match rocket.figment().extract::<AppConfig>() {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Invalid configuration: {}", e);
+                    return Err(rocket);
+                }
+            };
+
+{{#unless (eq template "minimal")}}
+            let tls_enabled = rocket
+                .figment()
+                .extract::<rocket::Config>()
+                .is_ok_and(|rocket_config| rocket_config.tls_enabled());
+            config.auth.secure_cookies.get_or_insert(tls_enabled);
+
+{{/unless}}
+            let problems = config.problems();
+            if !problems.is_empty() {
+                error!("Invalid configuration:");
+                for problem in &problems {
+                    error!("  - {}", problem);
+                }
+                return Err(rocket);
+            }
+
+            Ok(rocket
+{{#unless (eq template "minimal")}}
+                .manage(config.auth.clone())
+{{/unless}}
+                .manage(config.cors.clone())
+                .manage(config))
+        })
+    }
+
+    /// Lists every missing or invalid setting, so all of them can be fixed at once.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+{{#unless (eq template "minimal")}}
+        let mut missing = Vec::new();
+
+        if self.database.url.is_empty() {
+            missing.push("DATABASE_URL".to_string());
+        }
+{{#if (eq template "mongodb")}}
+        if self.database.name.is_empty() {
+            missing.push("DATABASE".to_string());
+        }
+{{/if}}
+        if self.auth.secret.is_empty() {
+            missing.push(self.auth.secret_env.clone());
+        }
+        if self.auth.issuer.is_empty() {
+            missing.push("auth.issuer".to_string());
+        }
+        if self.auth.audience.is_empty() {
+            missing.push("auth.audience".to_string());
+        }
+
+        if !missing.is_empty() {
+            problems.push(format!("missing keys: {}", missing.join(", ")));
+        }
+        if let Err(e) = self.auth.argon2.params() {
+            problems.push(format!("auth.argon2: {}", e));
+        }
+
+{{/unless}}
+        if self.cors.allows_any_origin_with_credentials() {
+            problems.push("cors: allow_credentials cannot be combined with the \"*\" origin".into());
+        }
+
+        problems
+    }
+}
+{{#unless (eq template "minimal")}}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_missing_key_is_reported() {
+        let problems = AppConfig::default().problems();
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("DATABASE_URL"));
+        assert!(problems[0].contains("AUTH_KEY"));
+        assert!(problems[0].contains("auth.issuer"));
+    }
 }
+{{/unless}}
 "#;
 
 pub const MIDDLEWARE: &str = r#"/* Middleware goes here */"#;
 
-pub const BASIC_AUTH: &str = r#"use crate::models::{LoginCredentials, User};
+pub const BASIC_AUTH: &str = r#"use crate::models::{LoginCredentials, TokenResponse, User};
 
-use bcrypt::{DEFAULT_COST, hash, verify};
-use chrono::{Duration, Utc};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::{Argon2, Params, Version};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{
     Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode, errors::ErrorKind,
 };
+use rand::RngCore;
+use rocket::http::{Cookie, CookieJar, SameSite};
+use rocket::time;
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use std::collections::HashSet;
+/// Token settings from the `[default.auth]` section of Rocket.toml.
+///
+/// The signing secret is never stored in Rocket.toml: `config::figment()`
+/// reads it from the environment variable named by `secret_env` (`AUTH_KEY`
+/// by default).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub issuer: String,
+    pub audience: String,
+    pub secret_env: String,
+    pub access_token_lifetime_minutes: i64,
+    pub refresh_token_lifetime_days: i64,
+    pub argon2: Argon2Config,
+    /// Marks the auth cookies `Secure`. Follows whether Rocket serves TLS when
+    /// unset; set it to `true` when TLS is terminated by a proxy.
+    pub secure_cookies: Option<bool>,
+    pub(crate) secret: String,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            issuer: String::new(),
+            audience: String::new(),
+            secret_env: "AUTH_KEY".to_string(),
+            access_token_lifetime_minutes: 15,
+            refresh_token_lifetime_days: 30,
+            argon2: Argon2Config::default(),
+            secure_cookies: None,
+            secret: String::new(),
+        }
+    }
+}
+
+/// Argon2id cost parameters from `[default.auth.argon2]`.
+///
+/// Raising them takes effect for existing users on their next login.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Argon2Config {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Config {
+    pub(crate) fn params(&self) -> Result<Params, String> {
+        Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|e| e.to_string())
+    }
+
+    fn hasher(&self) -> Result<Argon2<'static>, String> {
+        Ok(Argon2::new(
+            argon2::Algorithm::Argon2id,
+            Version::V0x13,
+            self.params()?,
+        ))
+    }
+}
 
 /// JWT claims structure, including subject, expiration, and unique nonce.
 #[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: String,      // Subject (user email)
-    exp: usize,       // Expiration timestamp
-    nonce: String,    // Unique secret marker
-    aud: Vec<String>, // Audience restriction
-    iss: String,      // Issuer restriction
-}
-
-/// Authorizes a user by verifying credentials and generating a JWT.
-pub async fn authorize_user(user: &User, credentials: &LoginCredentials) -> Result<String, String> {
-    let auth_key = std::env::var_os("AUTH_KEY")
-        .expect("[AUTH_KEY] must be set...")
-        .into_string()
-        .unwrap();
-
-    // Verify the provided password against the stored hash.
-    if !verify(&credentials.password, &user.password).map_err(|e| e.to_string())? {
+pub struct Claims {
+    pub sub: String,   // Subject (user email)
+    pub sid: String,   // Session (refresh token family) the token belongs to
+    #[serde(default)]
+    pub roles: Vec<String>, // Roles granted to the subject
+    pub exp: usize,    // Expiration timestamp
+    pub iat: usize,    // Issued-at timestamp
+    pub nonce: String, // Unique secret marker
+    pub aud: String,   // Audience restriction
+    pub iss: String,   // Issuer restriction
+}
+
+/// An opaque refresh token. Only `hash` is ever persisted.
+pub struct RefreshToken {
+    pub token: String,
+    pub hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Verifies a user's password against the stored hash.
+pub async fn authorize_user(user: &User, credentials: &LoginCredentials) -> Result<(), String> {
+    if !verify_password(&credentials.password, &user.password)? {
         return Err("Invalid credentials".into());
     }
 
-    // Generate a unique per-token nonce using user email and secret key.
+    Ok(())
+}
+
+/// Generates a new random refresh token that expires after the configured lifetime.
+pub fn new_refresh_token(config: &AuthConfig) -> RefreshToken {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    RefreshToken {
+        hash: hash_refresh_token(&token),
+        expires_at: Utc::now() + Duration::days(config.refresh_token_lifetime_days),
+        token,
+    }
+}
+
+/// Hashes a refresh token for storage and lookup.
+pub fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Sets the access and refresh token cookies (HTTP-only).
+pub fn set_auth_cookies(
+    cookies: &CookieJar<'_>,
+    access_token: &str,
+    refresh_token: &RefreshToken,
+    config: &AuthConfig,
+) {
+    cookies.add(
+        Cookie::build(("auth_token", access_token.to_string()))
+            .http_only(true)
+            .secure(config.secure_cookies.unwrap_or(false))
+            .same_site(SameSite::Lax)
+            .path("/")
+            .max_age(time::Duration::minutes(config.access_token_lifetime_minutes)),
+    );
+
+    cookies.add(
+        Cookie::build(("refresh_token", refresh_token.token.clone()))
+            .http_only(true)
+            .secure(config.secure_cookies.unwrap_or(false))
+            .same_site(SameSite::Strict)
+            .path("/")
+            .max_age(time::Duration::days(config.refresh_token_lifetime_days)),
+    );
+}
+
+/// Builds the token body returned to clients that cannot use cookies.
+pub fn token_response(
+    access_token: String,
+    refresh_token: &RefreshToken,
+    config: &AuthConfig,
+) -> TokenResponse {
+    TokenResponse {
+        token_type: "Bearer".to_string(),
+        access_token,
+        refresh_token: refresh_token.token.clone(),
+        expires_in: config.access_token_lifetime_minutes * 60,
+    }
+}
+
+/// Removes both authentication cookies.
+pub fn remove_auth_cookies(cookies: &CookieJar<'_>) {
+    cookies.remove(Cookie::build("auth_token").path("/"));
+    cookies.remove(Cookie::build("refresh_token").path("/"));
+}
+
+/// Issues a short-lived access JWT for `subject` within session `session_id`.
+pub fn issue_token(
+    subject: &str,
+    session_id: &str,
+    roles: &[String],
+    config: &AuthConfig,
+) -> Result<String, String> {
+    // Generate a unique per-token nonce using the subject and secret key.
     let mut hasher = Sha256::new();
-    hasher.update(format!("{}{}", user.email, auth_key));
+    hasher.update(format!("{}{}", subject, config.secret));
     let nonce = format!("{:x}", hasher.finalize());
 
-    // Set token expiration to 48 hours from now.
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::minutes(2880)) // 2880 minutes = 48 hours
-        .expect("valid timestamp")
-        .timestamp() as usize;
+    let now = Utc::now();
+    let expiration = now
+        .checked_add_signed(Duration::minutes(config.access_token_lifetime_minutes))
+        .ok_or("Invalid token lifetime")?;
 
-    // Create JWT claims.
     let claims = Claims {
-        sub: user.email.clone(),
-        exp: expiration,
+        sub: subject.to_string(),
+        sid: session_id.to_string(),
+        roles: roles.to_vec(),
+        exp: expiration.timestamp() as usize,
+        iat: now.timestamp() as usize,
         nonce,
-        aud: vec!["".to_string()], // Define your audience
-        iss: "".to_string(),       // Define your issuer
+        aud: config.audience.clone(),
+        iss: config.issuer.clone(),
     };
 
     // Encode claims into a JWT using HS256 algorithm and the secret key.
-    let token = encode(
+    encode(
         &Header::new(Algorithm::HS256),
         &claims,
-        &EncodingKey::from_secret(auth_key.as_ref()),
+        &EncodingKey::from_secret(config.secret.as_bytes()),
     )
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| e.to_string())
+}
+
+/// Hashes a given password using Argon2id with the configured cost.
+pub fn hash_password(password: &str, config: &AuthConfig) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
 
-    Ok(token)
+    config
+        .argon2
+        .hasher()?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
 }
 
-/// Hashes a given password using bcrypt with default cost.
-pub fn hash_password(password: String) -> Result<String, String> {
-    hash(password, DEFAULT_COST).map_err(|e| e.to_string())
+/// Verifies a password against an Argon2 hash or a legacy bcrypt hash.
+pub fn verify_password(password: &str, stored_hash: &str) -> Result<bool, String> {
+    if is_bcrypt_hash(stored_hash) {
+        return bcrypt::verify(password, stored_hash).map_err(|e| e.to_string());
+    }
+
+    let parsed = PasswordHash::new(stored_hash).map_err(|e| e.to_string())?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
 }
 
-/// Validates session JWTs
-pub async fn validate_token(token: &str) -> Result<String, String> {
-    let auth_key = std::env::var("AUTH_KEY").map_err(|_| "AUTH_KEY must be set".to_string())?;
+/// Whether a stored hash should be replaced on the next successful login:
+/// legacy bcrypt hashes and Argon2 hashes made with other parameters.
+pub fn needs_rehash(stored_hash: &str, config: &AuthConfig) -> bool {
+    if is_bcrypt_hash(stored_hash) {
+        return true;
+    }
 
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.set_audience(&["your-audience"]);
+    let (Ok(parsed), Ok(wanted)) = (PasswordHash::new(stored_hash), config.argon2.params()) else {
+        return true;
+    };
 
-    let mut iss_set = HashSet::new();
-    iss_set.insert("your-issuer".to_string());
-    validation.iss = Some(iss_set);
+    parsed.algorithm != argon2::Algorithm::Argon2id.ident()
+        || Params::try_from(&parsed).map_or(true, |current| {
+            current.m_cost() != wanted.m_cost()
+                || current.t_cost() != wanted.t_cost()
+                || current.p_cost() != wanted.p_cost()
+        })
+}
+
+fn is_bcrypt_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+/// Validates session JWTs against the configured issuer and audience.
+pub fn validate_token(token: &str, config: &AuthConfig) -> Result<Claims, String> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_audience(&[&config.audience]);
+    validation.set_issuer(&[&config.issuer]);
 
     let decoded = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(auth_key.as_bytes()),
+        &DecodingKey::from_secret(config.secret.as_bytes()),
         &validation,
     )
     .map_err(|e| match e.kind() {
@@ -156,12 +777,95 @@ pub async fn validate_token(token: &str) -> Result<String, String> {
         _ => format!("Token error: {}", e),
     })?;
 
-    Ok(decoded.claims.sub)
+    Ok(decoded.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AuthConfig {
+        AuthConfig {
+            issuer: "{{project_name}}".to_string(),
+            audience: "{{project_name}}-api".to_string(),
+            access_token_lifetime_minutes: 5,
+            refresh_token_lifetime_days: 1,
+            // Cheap parameters keep the tests fast.
+            argon2: Argon2Config {
+                memory_kib: 1024,
+                iterations: 1,
+                parallelism: 1,
+            },
+            secret: "test-secret".to_string(),
+            ..AuthConfig::default()
+        }
+    }
+
+    #[test]
+    fn issued_token_validates() {
+        let config = config();
+        let token = issue_token("user@example.com", "session-1", &[], &config).unwrap();
+        let claims = validate_token(&token, &config).unwrap();
+
+        assert_eq!(claims.sub, "user@example.com");
+        assert_eq!(claims.sid, "session-1");
+        assert_eq!(claims.iss, config.issuer);
+        assert_eq!(claims.aud, config.audience);
+    }
+
+    #[test]
+    fn token_from_another_issuer_is_rejected() {
+        let token = issue_token("user@example.com", "session-1", &[], &config()).unwrap();
+        let other = AuthConfig {
+            issuer: "someone-else".to_string(),
+            ..config()
+        };
+
+        assert!(validate_token(&token, &other).is_err());
+    }
+
+    #[test]
+    fn refresh_tokens_are_unique_and_hashed() {
+        let first = new_refresh_token(&config());
+        let second = new_refresh_token(&config());
+
+        assert_ne!(first.token, second.token);
+        assert_ne!(first.token, first.hash);
+        assert_eq!(first.hash, hash_refresh_token(&first.token));
+    }
+
+    #[test]
+    fn passwords_are_hashed_with_argon2id() {
+        let config = config();
+        let hash = hash_password("hunter2", &config).unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("hunter2", &hash).unwrap());
+        assert!(!verify_password("wrong", &hash).unwrap());
+        assert!(!needs_rehash(&hash, &config));
+
+        let mut stronger = config.clone();
+        stronger.argon2.iterations += 1;
+        assert!(needs_rehash(&hash, &stronger));
+    }
+
+    #[test]
+    fn legacy_bcrypt_hashes_verify_and_need_rehash() {
+        let hash = bcrypt::hash("hunter2", 4).unwrap();
+
+        assert!(verify_password("hunter2", &hash).unwrap());
+        assert!(!verify_password("wrong", &hash).unwrap());
+        assert!(needs_rehash(&hash, &config()));
+    }
 }
 "#;
 
 pub const GITIGNORE: &str = r#"/target
 .env
+.env.*
+!.env.example
+dist/
+/certs/
 "#;
 
 pub const ENV: &str = r#"# -------------------------------------------------------------------------
@@ -209,42 +913,1219 @@ DATABASE=
 AUTH_KEY=
 "#;
 
-pub const ROCKET_CONFIG: &str = r#"
+pub const ROCKET_CONFIG: &str = r#"# Rocket picks the `debug` profile for debug builds and `release` for
+# release builds (override with ROCKET_PROFILE); `[default]` applies to both.
+# See https://rocket.rs/guide/v0.5/configuration/
+
 [default]
 # Network settings
-address = "0.0.0.0"               # Listen on all network interfaces
 port = 8000                       # Port number
-workers = 16                      # Number of threads for request handling (adjust to number of CPU cores)
+{{#if workers}}
+# Threads for request handling
+workers = {{workers}}
+{{else}}
+# workers = 16                    # Threads for request handling (defaults to the number of CPU cores)
+{{/if}}
 keep_alive = 5                    # Keep-alive timeout in seconds
 max_blocking = 512                # Maximum number of blocking operations allowed simultaneously
 temp_dir = "/tmp"                 # Directory for temporary files
 ident = "Rocket"                  # Server identifier in responses
 
 # Logging and debugging
-log_level = "normal"            # Logging level: "critical", "normal", "debug"
+log_level = "normal"              # Logging level: "critical", "normal", "debug", "off"
 cli_colors = true                 # Enable CLI colors for local logs
 
 # Security
-ip_header = "X-Real-IP"           # Use reverse proxy header for client IP detection (set to "false" if unused)
+ip_header = "X-Real-IP"           # Use reverse proxy header for client IP detection (set to false if unused)
+
+# CORS (origins must match the browser's Origin header exactly)
+[default.cors]
+allowed_origins = [{{#if cors_origins}}{{#each cors_origins}}{{{toml_string this}}}{{#unless @last}}, {{/unless}}{{/each}}{{else}}"http://localhost:3000"{{/if}}]
+allowed_methods = ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]
+allowed_headers = ["Content-Type", "Authorization"]
+max_age = 86400                   # Seconds browsers may cache preflight responses
+# Allow cookies and Authorization headers (cannot be combined with "*")
+allow_credentials = {{#if (eq cors_credentials false)}}false{{else}}true{{/if}}
 
+{{#unless (eq template "minimal")}}
+# Database connection (the URL is read from DATABASE_URL in .env)
+[default.database]
+max_connections = 10              # Connection pool size
+connect_timeout_secs = 5          # Timeout for opening or acquiring a connection
+retry_for_secs = 30               # Keep retrying an unreachable database this long at startup
+{{#if features.migrations}}
+run_migrations = true             # Apply pending migrations from migrations/ at startup
+{{/if}}
+{{#if (eq template "mongodb")}}
+validate_schema = false           # Enforce the UserDocument $jsonSchema on the users collection
+{{/if}}
+
+# Optional behavior
+[default.features]
+registration = true               # Allow new accounts through POST /register
+
+# Authentication (the signing secret is read from the `secret_env` variable)
+[default.auth]
+issuer = "{{project_name}}"
+audience = "{{project_name}}-api"
+secret_env = "AUTH_KEY"
+access_token_lifetime_minutes = 15
+refresh_token_lifetime_days = 30
+# secure_cookies = true           # Defaults to whether TLS is enabled; set behind an HTTPS proxy
+
+# Argon2id password hashing cost (existing hashes are upgraded on next login)
+[default.auth.argon2]
+memory_kib = 19456                # Memory per hash in KiB
+iterations = 2                    # Number of passes
+parallelism = 1                   # Degree of parallelism
+
+{{/unless}}
 # Resource limits
 [default.limits]
-json = 52428800                 # Max size for JSON payloads (10 MB)
+json = 10485760                   # Max size for JSON payloads (10 MB)
 form = 2097152                    # Max size for form submissions (2 MB)
 file = 52428800                   # Max size for uploaded files (50 MB)
 
-# TLS configuration (uncomment and configure for HTTPS)
-[default.tls]
-certs = "/certs/client_cert.pem" # Path to TLS certificate
-key = "/certs/client_key.pem"    # Path to private key
+[debug]
+address = "127.0.0.1"             # Only reachable from this machine
+
+{{#if features.tls}}
+# TLS for local HTTPS (point these at your certificate and private key)
+[debug.tls]
+certs = "certs/cert.pem"          # Path to the PEM certificate chain
+key = "certs/key.pem"             # Path to the PEM private key
+{{else}}
+# TLS for local HTTPS (requires Rocket's "tls" feature; generate with --tls)
+# [debug.tls]
+# certs = "certs/cert.pem"        # Path to the PEM certificate chain
+# key = "certs/key.pem"           # Path to the PEM private key
+{{/if}}
+
+[release]
+address = "0.0.0.0"               # Listen on all network interfaces
+log_level = "critical"
+cli_colors = false
+
+# Signs private cookies (Rocket's "secrets" feature). Generate one with
+# `openssl rand -base64 32` and prefer ROCKET_SECRET_KEY over committing it.
+# secret_key = "<base64-encoded 256-bit key>"
+"#;
+pub const PAGINATION: &str = r#"use rocket::http::RawStr;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// `?page=&per_page=&sort=&q=` query parameters for list endpoints, e.g.
+/// `#[get("/items?<pagination..>")]`.
+#[derive(Debug, Clone, FromForm)]
+pub struct Pagination {
+    /// 1-based page number.
+    #[field(default = 1, validate = range(1..))]
+    pub page: u64,
+    #[field(default = 20, validate = range(1..=100))]
+    pub per_page: u64,
+    /// A field name, prefixed with `-` for descending order.
+    pub sort: Option<String>,
+    /// Free-text filter; each endpoint decides which fields it matches.
+    pub q: Option<String>,
+}
+
+/// A validated `?sort=`; `field` is always one of the endpoint's allowed fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub field: &'static str,
+    pub descending: bool,
+}
 
-[global]
-# Global overrides for all environments
-address = "0.0.0.0"
-port = 8000
+impl Pagination {
+    /// Number of items before the requested page.
+    pub fn offset(&self) -> u64 {
+        (self.page - 1) * self.per_page
+    }
+
+    /// The requested sort, or an error message if the field is not in `allowed`.
+    pub fn sort(&self, allowed: &[&'static str]) -> Result<Option<Sort>, String> {
+        let Some(sort) = self.sort.as_deref().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let (name, descending) = match sort.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (sort, false),
+        };
+
+        match allowed.iter().find(|field| **field == name) {
+            Some(field) => Ok(Some(Sort { field, descending })),
+            None => Err(format!(
+                "Cannot sort by '{}', expected one of: {}",
+                name,
+                allowed.join(", ")
+            )),
+        }
+    }
+
+    /// The trimmed search text, if any.
+    pub fn search(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
 
-[global.limits]
-json = 52428800
-form = 2097152
-file = 52428800
+    /// Link to `page` of `path`, keeping the other parameters.
+    fn link(&self, path: &str, page: u64) -> String {
+        let mut link = format!("{}?page={}&per_page={}", path, page, self.per_page);
+        if let Some(sort) = &self.sort {
+            link.push_str(&format!("&sort={}", RawStr::new(sort).percent_encode()));
+        }
+        if let Some(q) = self.search() {
+            link.push_str(&format!("&q={}", RawStr::new(q).percent_encode()));
+        }
+        link
+    }
+}
+
+/// Response envelope for one page of a list endpoint.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u64,
+    pub per_page: u64,
+    /// Number of matching items across all pages.
+    pub total: u64,
+    pub total_pages: u64,
+    pub links: PageLinks,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PageLinks {
+    #[serde(rename = "self")]
+    pub current: String,
+    pub first: String,
+    pub last: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Wraps the `items` of the page requested from `path`, out of `total` matches.
+    pub fn new(items: Vec<T>, total: u64, pagination: &Pagination, path: &str) -> Self {
+        let page = pagination.page;
+        let total_pages = total.div_ceil(pagination.per_page);
+        let last = total_pages.max(1);
+
+        Page {
+            items,
+            page,
+            per_page: pagination.per_page,
+            total,
+            total_pages,
+            links: PageLinks {
+                current: pagination.link(path, page),
+                first: pagination.link(path, 1),
+                last: pagination.link(path, last),
+                prev: (page > 1).then(|| pagination.link(path, (page - 1).min(last))),
+                next: (page < total_pages).then(|| pagination.link(path, page + 1)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagination(page: u64, sort: Option<&str>, q: Option<&str>) -> Pagination {
+        Pagination {
+            page,
+            per_page: 10,
+            sort: sort.map(str::to_string),
+            q: q.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn sort_accepts_only_allowed_fields() {
+        let allowed = &["name", "created_at"];
+
+        let sort = pagination(1, Some("-created_at"), None).sort(allowed);
+        assert_eq!(
+            sort,
+            Ok(Some(Sort {
+                field: "created_at",
+                descending: true
+            }))
+        );
+        assert_eq!(pagination(1, None, None).sort(allowed), Ok(None));
+        assert!(pagination(1, Some("password"), None).sort(allowed).is_err());
+    }
+
+    #[test]
+    fn links_keep_the_query_and_stay_in_range() {
+        let page = Page::new(vec![1, 2], 25, &pagination(2, Some("name"), Some("a&b")), "/items");
+
+        assert_eq!(page.total_pages, 3);
+        assert_eq!(page.links.current, "/items?page=2&per_page=10&sort=name&q=a%26b");
+        assert_eq!(page.links.prev.as_deref(), Some("/items?page=1&per_page=10&sort=name&q=a%26b"));
+        assert_eq!(page.links.last, "/items?page=3&per_page=10&sort=name&q=a%26b");
+        assert!(page.links.next.is_some());
+
+        let empty = Page::<u8>::new(Vec::new(), 0, &pagination(1, None, None), "/items");
+        assert_eq!(empty.total_pages, 0);
+        assert_eq!(empty.links.last, "/items?page=1&per_page=10");
+        assert!(empty.links.prev.is_none() && empty.links.next.is_none());
+    }
+}
+"#;
+
+pub const VALIDATION: &str = r#"use regex::Regex;
+use rocket::data::{self, Data, FromData};
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::Request;
+use rocket::serde::json::{self, Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::sync::LazyLock;
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap());
+
+/// A check applied to a string field.
+pub enum Rule {
+    /// Length in characters, inclusive.
+    Length { min: usize, max: usize },
+    /// A plausible email address: `local@domain.tld` without whitespace.
+    Email,
+    /// Must match `pattern`; `description` completes "must ..." in the error.
+    Matches {
+        pattern: &'static LazyLock<Regex>,
+        description: &'static str,
+    },
+}
+
+impl Rule {
+    fn check(&self, value: &str) -> Option<String> {
+        match self {
+            Rule::Length { min, max } => {
+                let length = value.chars().count();
+                (length < *min || length > *max)
+                    .then(|| format!("must be between {} and {} characters", min, max))
+            }
+            Rule::Email => {
+                (!EMAIL.is_match(value)).then(|| "must be a valid email address".to_string())
+            }
+            Rule::Matches {
+                pattern,
+                description,
+            } => (!pattern.is_match(value)).then(|| format!("must {}", description)),
+        }
+    }
+}
+
+/// Problems found in a payload, keyed by field name.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+#[serde(transparent)]
+pub struct FieldErrors(BTreeMap<&'static str, Vec<String>>);
+
+impl FieldErrors {
+    /// Applies `rules` to `value`, recording every failure under `field`.
+    pub fn check(mut self, field: &'static str, value: &str, rules: &[Rule]) -> Self {
+        for rule in rules {
+            if let Some(problem) = rule.check(value) {
+                self.0.entry(field).or_default().push(problem);
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), FieldErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// Declarative validation of a request payload, enforced by [`Validated`].
+pub trait Validate {
+    fn validate(&self) -> Result<(), FieldErrors>;
+}
+
+/// Data guard that deserializes and validates a payload, e.g.
+/// `credentials: Validated<Json<RegistrationCredentials>>`.
+///
+/// Invalid payloads fail with 422; the errors are kept in the request's
+/// local cache for the catchers to report.
+pub struct Validated<T>(pub T);
+
+impl<T> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+// Only read through `Debug`, when Rocket logs the failed guard.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum ValidationError<'r> {
+    /// The body is not valid JSON for the payload type.
+    Json(json::Error<'r>),
+    Fields(FieldErrors),
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r> + Validate> FromData<'r> for Validated<Json<T>> {
+    type Error = ValidationError<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        match Json::<T>::from_data(req, data).await {
+            Outcome::Success(payload) => match payload.validate() {
+                Ok(()) => Outcome::Success(Validated(payload)),
+                Err(errors) => {
+                    req.local_cache(|| errors.clone());
+                    Outcome::Error((Status::UnprocessableEntity, ValidationError::Fields(errors)))
+                }
+            },
+            Outcome::Error((status, e)) => Outcome::Error((status, ValidationError::Json(e))),
+            Outcome::Forward(forward) => Outcome::Forward(forward),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_failed_rule_is_reported_per_field() {
+        let errors = FieldErrors::default()
+            .check("name", "", &[Rule::Length { min: 1, max: 3 }, Rule::Email])
+            .check("email", "jane@example.com", &[Rule::Email]);
+
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!(
+            errors.0["name"],
+            [
+                "must be between 1 and 3 characters",
+                "must be a valid email address"
+            ]
+        );
+        assert!(FieldErrors::default()
+            .check("email", "a@b.co", &[Rule::Email])
+            .into_result()
+            .is_ok());
+        assert!(FieldErrors::default()
+            .check("email", "a b@c.co", &[Rule::Email])
+            .into_result()
+            .is_err());
+    }
+}
+"#;
+
+pub const ERRORS: &str = r#"use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::Json;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::fairings::request_id;
+use crate::validation::FieldErrors;
+
+/// An error response, rendered as an RFC 7807 `application/problem+json` document.
+///
+/// Routes return it as `Result<Json<T>, ApiError>`, and the catchers use it
+/// for errors raised before a route runs (failed guards, unknown paths, ...).
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: Status,
+    /// Human-readable explanation specific to this occurrence.
+    pub detail: String,
+    /// Problems of each invalid field, for 422 responses.
+    pub errors: FieldErrors,
+}
+
+impl ApiError {
+    pub fn new(status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            detail: detail.into(),
+            errors: FieldErrors::default(),
+        }
+    }
+
+    /// A 500 that does not reveal the underlying failure.
+    pub fn internal() -> Self {
+        Self::new(
+            Status::InternalServerError,
+            "Something went wrong, please try again later",
+        )
+    }
+}
+
+/// The problem details document.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Problem<'a> {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Path of the request that failed.
+    pub instance: String,
+    /// Also sent as the `X-Request-Id` header, for matching reports with logs.
+    pub request_id: &'a str,
+    #[serde(skip_serializing_if = "FieldErrors::is_empty")]
+    pub errors: FieldErrors,
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let problem = Problem {
+            kind: "about:blank",
+            title: self.status.reason().unwrap_or("Unknown Error"),
+            status: self.status.code,
+            detail: self.detail,
+            instance: req.uri().path().to_string(),
+            request_id: request_id(req),
+            errors: self.errors,
+        };
+
+        Response::build_from(Json(problem).respond_to(req)?)
+            .status(self.status)
+            .header(ContentType::new("application", "problem+json"))
+            .ok()
+    }
+}
+"#;
+
+pub const CATCHERS: &str = r#"use rocket::http::Status;
+use rocket::{Catcher, Request};
+
+use crate::errors::ApiError;
+use crate::validation::FieldErrors;
+
+/// Every catcher, for `.register("/", catchers::all())`.
+pub fn all() -> Vec<Catcher> {
+    catchers![problem]
+}
+
+/// Renders any error status that reaches a catcher as a problem document.
+#[catch(default)]
+fn problem(status: Status, req: &Request<'_>) -> ApiError {
+    // Recorded by the `Validated` data guard when a payload is invalid.
+    let errors = req.local_cache(FieldErrors::default).clone();
+    let detail = match status.code {
+        401 => "Authentication is required to access this resource.",
+        403 => "You don't have permission to access this resource.",
+        404 => "Resource not found.",
+        409 => "The request could not be completed due to a conflict.",
+        422 if !errors.is_empty() => "Validation failed.",
+        422 => "The request body could not be processed.",
+        500 => "Something went wrong, please try again later.",
+        _ => status.reason().unwrap_or("The request failed."),
+    };
+
+    ApiError {
+        status,
+        detail: detail.to_string(),
+        errors,
+    }
+}
+"#;
+
+pub const OPENAPI: &str = r#"{{#if features.openapi}}
+//! OpenAPI 3 document of the application, served at `/openapi.json`.
+//!
+//! Every mounted route is documented. Schemas come from the models'
+//! `JsonSchema` derives, and [`describe`] adds what a route can't tell about
+//! itself; a test fails when a route has no description yet.
+
+use rocket::fairing::AdHoc;
+use rocket::http::Method;
+use rocket::response::content::RawHtml;
+use rocket::serde::json::{self, json, Value};
+use rocket::{Route, State};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use std::path::Path;
+
+use crate::errors::Problem;
+use crate::health::{Health, Version};
+use crate::models::{LoginCredentials, LoginResponse, RefreshRequest, RegistrationCredentials};
+use crate::models::{SuccessResponse, UserInfo};
+{{#if (eq template "mongodb")}}
+use crate::models::UserDocument as StoredUser;
+{{else}}
+use crate::models::UserEntity as StoredUser;
+{{/if}}
+use crate::pagination::Page;
+
+/// Set by `rocket-cli openapi export` to the file the document is written to.
+pub const EXPORT_ENV: &str = "{{cli.openapi_export_env}}";
+
+/// The document's own routes, which are left out of it.
+const OWN_PATHS: &[&str] = &["/openapi.json", "/docs"];
+
+const DOCS_HTML: &str = "<!doctype html>
+<html>
+  <head>
+    <meta charset='utf-8'>
+    <title>{{project_name}} API</title>
+    <script type='module' src='https://unpkg.com/rapidoc/dist/rapidoc-min.js'></script>
+  </head>
+  <body>
+    <rapi-doc spec-url='/openapi.json' render-style='read' show-header='false'></rapi-doc>
+  </body>
+</html>
+";
+
+/// The document and its RapiDoc UI at `/docs`.
+pub fn routes() -> Vec<Route> {
+    routes![spec, docs]
+}
+
+/// The document, built from the mounted routes by [`fairing`].
+struct Spec(Value);
+
+#[get("/openapi.json")]
+fn spec(spec: &State<Spec>) -> Value {
+    spec.0.clone()
+}
+
+#[get("/docs")]
+fn docs() -> RawHtml<&'static str> {
+    RawHtml(DOCS_HTML)
+}
+
+/// What a route can't tell about itself.
+struct Description {
+    summary: &'static str,
+    /// Names and JSON types of the optional query parameters, including the
+    /// fields of a `<param..>` struct.
+    query: &'static [(&'static str, &'static str)],
+    body: Option<Schema>,
+    response: Schema,
+    /// Requires an access token, as a bearer token or the `auth_token` cookie.
+    secured: bool,
+}
+
+/// Describes the route for `method` and `path`, in OpenAPI's `{param}` syntax.
+fn describe(gen: &mut SchemaGenerator, method: Method, path: &str) -> Option<Description> {
+    let description = match (method, path) {
+        (Method::Post, "/register") => Description {
+            summary: "Register a new user",
+            query: &[],
+            body: Some(gen.subschema_for::<RegistrationCredentials>()),
+            response: gen.subschema_for::<SuccessResponse>(),
+            secured: false,
+        },
+        (Method::Post, "/login") => Description {
+            summary: "Log in and set the authentication cookies",
+            query: &[("include_token", "boolean")],
+            body: Some(gen.subschema_for::<LoginCredentials>()),
+            response: gen.subschema_for::<LoginResponse>(),
+            secured: false,
+        },
+        (Method::Post, "/refresh") => Description {
+            summary: "Exchange a refresh token (body or cookie) for new tokens",
+            query: &[],
+            body: Some(gen.subschema_for::<RefreshRequest>()),
+            response: gen.subschema_for::<LoginResponse>(),
+            secured: false,
+        },
+        (Method::Post, "/logout") => Description {
+            summary: "End the current session",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<SuccessResponse>(),
+            secured: false,
+        },
+        (Method::Get, "/users") => Description {
+            summary: "List users a page at a time (admins only)",
+            query: &[
+                ("page", "integer"),
+                ("per_page", "integer"),
+                ("sort", "string"),
+                ("q", "string"),
+            ],
+            body: None,
+            response: gen.subschema_for::<Page<UserInfo>>(),
+            secured: true,
+        },
+        (Method::Get, "/users/{id}") => Description {
+            summary: "Get a user by id",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<StoredUser>(),
+            secured: true,
+        },
+        (Method::Get, "/user/{email}") => Description {
+            summary: "Get a user by email",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<UserInfo>(),
+            secured: true,
+        },
+        (Method::Put, "/update/{id}") => Description {
+            summary: "Update your own account, or any account as an admin",
+            query: &[],
+            body: Some(gen.subschema_for::<RegistrationCredentials>()),
+            response: gen.subschema_for::<StoredUser>(),
+            secured: true,
+        },
+        (Method::Delete, "/delete/{id}") => Description {
+            summary: "Delete your own account, or any account as an admin",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<SuccessResponse>(),
+            secured: true,
+        },
+        (Method::Get, "/health/live") => Description {
+            summary: "Liveness probe",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<Health>(),
+            secured: false,
+        },
+        (Method::Get, "/health/ready") => Description {
+            summary: "Readiness probe: fails with 503 while the database is unreachable",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<Health>(),
+            secured: false,
+        },
+        (Method::Get, "/version") => Description {
+            summary: "Crate name, version and the git commit it was built from",
+            query: &[],
+            body: None,
+            response: gen.subschema_for::<Version>(),
+            secured: false,
+        },
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// The route's path in OpenAPI's `{param}` syntax.
+fn openapi_path(route: &Route) -> String {
+    route
+        .uri
+        .path()
+        .replace('<', "{")
+        .replace("..>", "}")
+        .replace('>', "}")
+}
+
+/// Names of the `<param>` query segments of the route; `<param..>` structs are skipped.
+fn query_parameters(route: &Route) -> Vec<&str> {
+    route
+        .uri
+        .query()
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter_map(|segment| segment.strip_prefix('<')?.strip_suffix('>'))
+        .filter(|name| !name.ends_with(".."))
+        .collect()
+}
+
+/// Names of the `{param}` segments of `path`.
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+}
+
+/// The OpenAPI document of `routes`.
+pub fn document<'a>(routes: impl Iterator<Item = &'a Route>) -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let problem = gen.subschema_for::<Problem<'static>>();
+    let mut paths = json::serde_json::Map::new();
+
+    for route in routes {
+        let path = openapi_path(route);
+        if OWN_PATHS.contains(&path.as_str()) {
+            continue;
+        }
+        let description = describe(&mut gen, route.method, &path);
+
+        // Described types win; other query parameters are documented as strings.
+        let described_query = description.as_ref().map_or(&[][..], |d| d.query);
+        let query = query_parameters(route)
+            .into_iter()
+            .filter(|name| !described_query.iter().any(|(known, _)| known == name))
+            .map(|name| (name, "string"))
+            .chain(described_query.iter().copied());
+        let parameters = path_parameters(&path)
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+            .chain(query.map(|(name, kind)| {
+                json!({ "name": name, "in": "query", "required": false, "schema": { "type": kind } })
+            }))
+            .collect::<Vec<_>>();
+
+        let mut item = json!({
+            "summary": route.name.as_deref().unwrap_or_default(),
+            "parameters": parameters,
+            "responses": {
+                "200": { "description": "Success" },
+                "default": {
+                    "description": "Error, as RFC 7807 problem details",
+                    "content": { "application/problem+json": { "schema": problem } },
+                },
+            },
+        });
+        if let Some(description) = description {
+            item["summary"] = json!(description.summary);
+            item["responses"]["200"]["content"] =
+                json!({ "application/json": { "schema": description.response } });
+            if let Some(body) = description.body {
+                item["requestBody"] = json!({
+                    "required": true,
+                    "content": { "application/json": { "schema": body } },
+                });
+            }
+            if description.secured {
+                item["security"] = json!([{ "bearerAuth": [] }, { "cookieAuth": [] }]);
+            }
+        }
+
+        let method = route.method.as_str().to_ascii_lowercase();
+        paths.entry(path).or_insert_with(|| json!({}))[method] = item;
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": "{{project_name}}", "version": env!("CARGO_PKG_VERSION") },
+        "paths": paths,
+        "components": {
+            "schemas": gen.take_definitions(),
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "cookieAuth": { "type": "apiKey", "in": "cookie", "name": "auth_token" },
+            },
+        },
+    })
+}
+
+/// Builds the document once the routes are mounted, for `/openapi.json`.
+///
+/// When [`EXPORT_ENV`] is set, writes it there and exits instead of
+/// launching. Attach it first: the application's other fairings, such as the
+/// database connection, don't run for an export.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("OpenAPI", |rocket| async move {
+        let document = document(rocket.routes());
+        let Some(path) = std::env::var_os(EXPORT_ENV) else {
+            return rocket.manage(Spec(document));
+        };
+
+        let document = json::to_pretty_string(&document).expect("the document is valid JSON");
+        if let Err(e) = std::fs::write(&path, document + "\n") {
+            eprintln!("Failed to write {}: {}", Path::new(&path).display(), e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_mounted_route_is_described() {
+        let rocket = crate::rocket();
+        let mut gen = SchemaSettings::openapi3().into_generator();
+
+        for route in rocket.routes() {
+            let path = openapi_path(route);
+            if OWN_PATHS.contains(&path.as_str()) {
+                continue;
+            }
+            assert!(
+                describe(&mut gen, route.method, &path).is_some(),
+                "{} {} has no description in the OpenAPI document",
+                route.method,
+                path
+            );
+        }
+
+        let document = document(rocket.routes());
+        assert!(document["paths"]["/users/{id}"]["get"]["security"].is_array());
+        assert!(document["paths"]["/login"]["post"]["parameters"][0]["name"] == "include_token");
+        assert!(document["components"]["schemas"]["RegistrationCredentials"].is_object());
+    }
+}
+{{/if}}
+"#;
+
+pub const HEALTH: &str = r#"//! Probes for orchestrators such as Kubernetes, and the build's version.
+//!
+{{#if (eq template "minimal")}}
+//! Without a database, `/health/ready` answers like `/health/live` once launched.
+{{else}}
+//! `/health/live` only shows the process is serving requests; `/health/ready`
+//! also checks that the database answers, so no traffic is routed while it is down.
+{{/if}}
+
+{{#unless (eq template "minimal")}}
+use rocket::http::Status;
+{{/unless}}
+use rocket::serde::json::Json;
+{{#unless (eq template "minimal")}}
+use rocket::State;
+use schemars::JsonSchema;
+{{/unless}}
+use serde::Serialize;
+{{#unless (eq template "minimal")}}
+use std::sync::Arc;
+
+use crate::errors::ApiError;
+use crate::repositories::UserStore;
+{{/unless}}
+
+/// The health routes and `/version`.
+pub fn routes() -> Vec<rocket::Route> {
+    routes![live, ready, version]
+}
+
+#[derive(Debug, Serialize{{#unless (eq template "minimal")}}, JsonSchema{{/unless}})]
+pub struct Health {
+    pub status: &'static str,
+}
+
+impl Health {
+    const OK: Health = Health { status: "ok" };
+}
+
+#[derive(Debug, Serialize{{#unless (eq template "minimal")}}, JsonSchema{{/unless}})]
+pub struct Version {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Commit the binary was built from, set by `build.rs`.
+    pub git_sha: &'static str,
+}
+
+/// Liveness probe: the process is up and serving requests.
+#[get("/health/live")]
+pub fn live() -> Json<Health> {
+    Json(Health::OK)
+}
+
+{{#if (eq template "minimal")}}
+/// Readiness probe: the application has launched and can take traffic.
+#[get("/health/ready")]
+pub fn ready() -> Json<Health> {
+    Json(Health::OK)
+}
+{{else}}
+/// Readiness probe: the database answers, so requests can be served.
+#[get("/health/ready")]
+pub async fn ready(repo: &State<Arc<dyn UserStore>>) -> Result<Json<Health>, ApiError> {
+    match repo.ping().await {
+        Ok(()) => Ok(Json(Health::OK)),
+        Err(e) => {
+            warn!("Readiness check failed: {}", e);
+            Err(ApiError::new(
+                Status::ServiceUnavailable,
+                "The database is unreachable",
+            ))
+        }
+    }
+}
+{{/if}}
+
+#[get("/version")]
+pub fn version() -> Json<Version> {
+    Json(Version {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("GIT_SHA"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+{{#unless (eq template "minimal")}}
+    use crate::repositories::memory::InMemoryUserStore;
+{{/unless}}
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+
+    #[test]
+    fn probes_and_version_answer() {
+{{#if (eq template "minimal")}}
+        let rocket = rocket::build().mount("/", routes());
+{{else}}
+        let store: Arc<dyn UserStore> = Arc::new(InMemoryUserStore::default());
+        let rocket = rocket::build().manage(store).mount("/", routes());
+{{/if}}
+        let client = Client::untracked(rocket).unwrap();
+
+        for path in ["/health/live", "/health/ready"] {
+            let response = client.get(path).dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.into_json::<Value>().unwrap()["status"], "ok");
+        }
+
+        let version: Value = client.get("/version").dispatch().into_json().unwrap();
+        assert_eq!(version["name"], env!("CARGO_PKG_NAME"));
+        assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!version["git_sha"].as_str().unwrap().is_empty());
+    }
+}
+"#;
+
+pub const BUILD_RS: &str = r#"use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    // `/version` reports the commit the binary was built from. `GIT_SHA` can be
+    // set explicitly where `.git` is unavailable, e.g. in container builds.
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    let sha = std::env::var("GIT_SHA")
+        .ok()
+        .or_else(git_sha)
+        .unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=GIT_SHA={}", sha);
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+{{#if features.migrations}}
+
+    // `migrations/` is embedded into the binary; rebuild whenever it changes.
+    println!("cargo:rerun-if-changed=migrations");
+{{/if}}
+}
+
+fn git_sha() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let sha = String::from_utf8(output.stdout).ok()?;
+    Some(sha.trim().to_string())
+}
+"#;
+
+pub const EMBEDDED_MIGRATIONS: &str = r#"{{#if features.migrations}}
+//! SQL migrations embedded from `migrations/` at compile time.
+//!
+//! Applied migrations are recorded in the same `schema_migrations` table as
+//! `rocket-cli db migrate`, so the CLI and the application can be mixed.
+
+use include_dir::{include_dir, Dir};
+use rbatis::RBatis;
+use rbs::Value;
+use serde::Deserialize;
+
+static MIGRATIONS: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/migrations");
+
+const CREATE_MIGRATIONS_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (
+    version BIGINT PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+)";
+
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+#[derive(Deserialize)]
+struct AppliedMigration {
+    version: i64,
+}
+
+/// Embedded `<version>_<name>.sql` files ordered by version; `.down.sql` files are skipped.
+fn embedded() -> Vec<Migration> {
+    let mut migrations: Vec<Migration> = MIGRATIONS
+        .files()
+        .filter_map(|file| {
+            let file_name = file.path().file_name()?.to_str()?;
+            if file_name.ends_with(".down.sql") {
+                return None;
+            }
+            let (version, name) = file_name.strip_suffix(".sql")?.split_once('_')?;
+            Some(Migration {
+                version: version.parse().ok()?,
+                name,
+                sql: file.contents_utf8()?,
+            })
+        })
+        .collect();
+    migrations.sort_by_key(|m| m.version);
+    migrations
+}
+
+/// Applies every pending migration, each in its own transaction.
+pub async fn run(rb: &RBatis) -> Result<(), rbatis::Error> {
+    rb.exec(CREATE_MIGRATIONS_TABLE, vec![]).await?;
+    let applied: Vec<AppliedMigration> = rb
+        .query_decode("SELECT version FROM schema_migrations", vec![])
+        .await?;
+
+    let mut count = 0;
+    for migration in embedded() {
+        if applied.iter().any(|a| a.version == migration.version) {
+            continue;
+        }
+
+        let tx = rb.acquire_begin().await?;
+        let result = async {
+            for statement in statements(migration.sql) {
+                tx.exec(statement, vec![]).await?;
+            }
+            tx.exec(
+                "INSERT INTO schema_migrations (version, name) VALUES (?, ?)",
+                vec![
+                    Value::I64(migration.version),
+                    Value::String(migration.name.to_string()),
+                ],
+            )
+            .await?;
+            Ok::<_, rbatis::Error>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            let _ = tx.rollback().await;
+            return Err(rbatis::Error::from(format!(
+                "migration {}_{} failed: {}",
+                migration.version, migration.name, e
+            )));
+        }
+        tx.commit().await?;
+
+        info!("Applied migration {}_{}", migration.version, migration.name);
+        count += 1;
+    }
+
+    if count == 0 {
+        info!("Database schema is up to date");
+    }
+    Ok(())
+}
+
+/// Splits a script into single statements, which is all a prepared query accepts.
+///
+/// Semicolons inside quotes, dollar-quoted (`$$` or `$tag$`) bodies and `--`
+/// comments are left alone.
+fn statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut quote: Option<&[u8]> = None;
+    let (mut start, mut i, mut has_code) = (0, 0, false);
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if let Some(closing) = quote {
+            if rest.starts_with(closing) {
+                quote = None;
+                i += closing.len();
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+
+        if rest.starts_with(b"--") {
+            i += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            continue;
+        }
+
+        match bytes[i] {
+            b'\'' => quote = Some(b"'"),
+            b'"' => quote = Some(b"\""),
+            b'$' => {
+                if let Some(len) = dollar_tag(rest) {
+                    // The body ends at the same tag it was opened with.
+                    quote = Some(&rest[..len]);
+                    has_code = true;
+                    i += len;
+                    continue;
+                }
+            }
+            b';' => {
+                if has_code {
+                    statements.push(sql[start..i].trim());
+                }
+                start = i + 1;
+                has_code = false;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        has_code |= !bytes[i].is_ascii_whitespace();
+        i += 1;
+    }
+
+    if has_code {
+        statements.push(sql[start..].trim());
+    }
+    statements
+}
+
+/// Length of the `$$` or `$tag$` opening a dollar-quoted string at the start of `rest`.
+///
+/// Tags follow identifier rules, so positional parameters such as `$1` don't match.
+fn dollar_tag(rest: &[u8]) -> Option<usize> {
+    let tag = rest[1..]
+        .iter()
+        .take_while(|&&b| b.is_ascii_alphanumeric() || b == b'_')
+        .count();
+    let starts_with_digit = rest.get(1).is_some_and(u8::is_ascii_digit);
+    (rest.get(tag + 1) == Some(&b'$') && !starts_with_digit).then_some(tag + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_statements_outside_quotes_and_comments() {
+        let sql = "-- header; not a statement\nCREATE TABLE a (b TEXT DEFAULT ';');\n\nINSERT INTO a VALUES ('it''s; fine');\n";
+        assert_eq!(
+            statements(sql),
+            [
+                "-- header; not a statement\nCREATE TABLE a (b TEXT DEFAULT ';')",
+                "INSERT INTO a VALUES ('it''s; fine')",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_tagged_dollar_quoted_bodies_together() {
+        let sql = "CREATE FUNCTION touch() RETURNS trigger AS $body$\nBEGIN\n  NEW.note := $$a;b$$;\n  RETURN NEW;\nEND;\n$body$ LANGUAGE plpgsql;\nSELECT $1;";
+        assert_eq!(
+            statements(sql),
+            [
+                "CREATE FUNCTION touch() RETURNS trigger AS $body$\nBEGIN\n  NEW.note := $$a;b$$;\n  RETURN NEW;\nEND;\n$body$ LANGUAGE plpgsql",
+                "SELECT $1",
+            ]
+        );
+    }
+
+    #[test]
+    fn embedded_migrations_are_ordered() {
+        let versions: Vec<i64> = embedded().iter().map(|m| m.version).collect();
+        assert!(!versions.is_empty());
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+    }
+}
+{{/if}}
 "#;
\ No newline at end of file
@@ -4,20 +4,25 @@ version = "0.1.0"
 edition = "2021"
 
 [dependencies]
-rocket = { version = "0.5.1", features = ["json"] }
+rocket = { version = "0.5.1", features = ["json"{{#if features.tls}}, "tls"{{/if}}] }
+serde = { version = "1.0.216", features = ["derive"] }
 "#;
 
 pub const MAIN_RS: &str = r#"#[macro_use] 
 extern crate rocket;
 
+mod config;
 mod fairings;
+mod health;
 mod routes;
 
 #[launch]
 fn rocket() -> _ {
-    rocket::build()
+    rocket::custom(config::figment())
+        .attach(config::AppConfig::fairing())
         .attach(fairings::Cors)
         .mount("/", routes::routes())
+        .mount("/", health::routes())
 }
 "#;
 
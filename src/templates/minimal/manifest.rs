@@ -7,7 +7,10 @@ pub fn load_template() -> Vec<(PathBuf, &'static str)> {
         ("Cargo.toml".into(), files::CARGO_TOML),
         ("src/main.rs".into(), files::MAIN_RS),
         ("src/routes/mod.rs".into(), files::ROUTES_MOD),
+        ("src/config/mod.rs".into(), common::files::CONFIG),
         ("src/fairings/mod.rs".into(), common::files::CORS),
+        ("src/health/mod.rs".into(), common::files::HEALTH),
+        ("build.rs".into(), common::files::BUILD_RS),
         ("Rocket.toml".into(), common::files::ROCKET_CONFIG),
         (".gitignore".into(), common::files::GITIGNORE),
         (".env".into(), common::files::ENV),
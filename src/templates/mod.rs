@@ -3,8 +3,63 @@ pub mod minimal;
 pub mod mongo_db;
 pub mod postgres;
 
+use serde::Serialize;
 use std::path::PathBuf;
 
+/// Environment variable that makes an application generated with `--openapi`
+/// write its OpenAPI document to the named file and exit instead of launching.
+///
+/// Templates render it as `{{cli.openapi_export_env}}`.
+pub const OPENAPI_EXPORT_ENV: &str = "OPENAPI_EXPORT";
+
+/// Metadata describing a template listed by `rocket-cli new --list`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TemplateInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub available: bool,
+    pub default: bool,
+}
+
+pub const TEMPLATES: &[TemplateInfo] = &[
+    TemplateInfo {
+        name: "minimal",
+        description: "Basic Rocket project with a single route",
+        available: true,
+        default: true,
+    },
+    TemplateInfo {
+        name: "mongodb",
+        description: "Rocket + MongoDB integration",
+        available: true,
+        default: false,
+    },
+    TemplateInfo {
+        name: "postgres",
+        description: "Rocket + PostgreSQL",
+        available: true,
+        default: false,
+    },
+    TemplateInfo {
+        name: "mysql",
+        description: "Rocket + MySQL",
+        available: false,
+        default: false,
+    },
+    TemplateInfo {
+        name: "mssql",
+        description: "Rocket + SQL Server",
+        available: false,
+        default: false,
+    },
+    TemplateInfo {
+        name: "sqlite",
+        description: "Rocket + SQLite",
+        available: false,
+        default: false,
+    },
+];
+
 pub fn load_template_files(template: &str) -> Option<Vec<(PathBuf, &'static str)>> {
     match template {
         "minimal" => Some(minimal::manifest::load_template()),
@@ -14,7 +14,7 @@ jsonwebtoken = "9.3.0"
 mongodb = "3.1.1"
 rand = "0.8.5"
 regex = "1.11.1"
-rocket = { version = "0.5.1", features = ["json"] }
+rocket = { version = "0.5.1", features = ["json"{{#if features.tls}}, "tls"{{/if}}] }
 schemars = "0.8.21"
 serde = { version = "1.0.216", features = ["derive"] }
 tokio = { version = "1.42.0", features = ["full"] }
@@ -26,140 +26,126 @@ extern crate rocket;
 
 mod auth;
 mod catchers;
+mod config;
 mod db;
+mod errors;
 mod fairings;
 mod guards;
+mod health;
 mod middleware;
 mod models;
-mod options;
+{{#if features.openapi}}
+mod openapi;
+{{/if}}
+mod pagination;
 mod repositories;
 mod routes;
+mod validation;
 
 #[launch]
 fn rocket() -> _ {
-    rocket::build()
+    rocket::custom(config::figment())
+{{#if features.openapi}}
+        .attach(openapi::fairing())
+{{/if}}
+        .attach(config::AppConfig::fairing())
         .attach(db::init())
         .attach(fairings::Cors)
-        .register(
-            "/",
-            catchers![
-                catchers::bad_request,
-                catchers::unauthorized,
-                catchers::forbidden,
-                catchers::not_found,
-                catchers::method_not_allowed,
-                catchers::request_timeout,
-                catchers::conflict,
-                catchers::payload_too_large,
-                catchers::unsupported_media_type,
-                catchers::teapot,
-                catchers::too_many_requests,
-                catchers::internal_error,
-                catchers::bad_gateway,
-                catchers::service_unavailable,
-                catchers::gateway_timeout
-            ],
-        )
-        .mount("/", routes![options::options])
+        .attach(fairings::RequestId)
+        .register("/", catchers::all())
         .mount("/", routes::user_routes())
+        .mount("/", health::routes())
+{{#if features.openapi}}
+        .mount("/", openapi::routes())
+{{/if}}
 }
 "#;
 
-pub const CATACHERS: &str = r#"use rocket::catch;
-
-#[catch(400)]
-pub async fn bad_request() -> &'static str {
-    "Bad Request."
-}
-
-#[catch(401)]
-pub async fn unauthorized() -> &'static str {
-    "Unauthorized access."
-}
-
-#[catch(403)]
-pub async fn forbidden() -> &'static str {
-    "You don't have permission to access this resource."
-}
-
-#[catch(404)]
-pub async fn not_found() -> &'static str {
-    "Resource not found."
-}
-
-#[catch(405)]
-pub async fn method_not_allowed() -> &'static str {
-    "Method Not Allowed."
-}
-
-#[catch(408)]
-pub async fn request_timeout() -> &'static str {
-    "Request Timeout."
-}
+pub const MODELS: &str = r#"use chrono::{DateTime, Utc};
+use mongodb::bson::{doc, DateTime as BsonDateTime, Document, oid::ObjectId};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
 
-#[catch(409)]
-pub async fn conflict() -> &'static str {
-    "The request could not be completed due to a conflict."
-}
+use crate::validation::{FieldErrors, Rule, Validate};
 
-#[catch(413)]
-pub async fn payload_too_large() -> &'static str {
-    "Payload Too Large."
-}
+/// Role given to newly registered users
+pub const DEFAULT_ROLE: &str = "user";
 
-#[catch(415)]
-pub async fn unsupported_media_type() -> &'static str {
-    "Unsupported Media Type."
-}
+/// Characters allowed in usernames
+static USERNAME: LazyLock<Regex> = LazyLock::new(|| Regex::new("^[A-Za-z0-9_.-]+$").unwrap());
 
-#[catch(418)]
-pub async fn teapot() -> &'static str {
-    "I'm a teapot."
+fn default_roles() -> Vec<String> {
+    vec![DEFAULT_ROLE.to_string()]
 }
 
-#[catch(429)]
-pub async fn too_many_requests() -> &'static str {
-    "Too Many Requests."
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct UserDocument {
+{{#each cli.user_fields}}
+{{#unless (eq key field)}}
+    #[serde(rename = "{{key}}")]
+{{/unless}}
+{{#each attributes}}
+    {{{this}}}
+{{/each}}
+    pub {{field}}: {{{rust_type}}},
+{{/each}}
 }
 
-#[catch(500)]
-pub async fn internal_error() -> &'static str {
-    "Internal Server Error."
+/// JSON schema of an `ObjectId` serialized outside BSON: `{ "$oid": "<hex>" }`.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct ObjectIdJson {
+    #[serde(rename = "$oid")]
+    oid: String,
 }
 
-#[catch(502)]
-pub async fn bad_gateway() -> &'static str {
-    "Bad Gateway."
+/// JSON schema of a BSON date serialized outside BSON:
+/// `{ "$date": { "$numberLong": "<milliseconds>" } }`.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct DateTimeJson {
+    #[serde(rename = "$date")]
+    date: NumberLong,
 }
 
-#[catch(503)]
-pub async fn service_unavailable() -> &'static str {
-    "Service Unavailable."
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct NumberLong {
+    #[serde(rename = "$numberLong")]
+    number_long: String,
 }
 
-#[catch(504)]
-pub async fn gateway_timeout() -> &'static str {
-    "Gateway Timeout."
+impl UserDocument {
+    /// `$jsonSchema` validator matching this struct's BSON representation.
+    ///
+    /// Read from `user_schema.json`, which is generated from the same field
+    /// list as this struct and which `rocket-cli db indexes --validator` also
+    /// installs.
+    pub fn json_schema() -> Document {
+        rocket::serde::json::serde_json::from_str(include_str!("user_schema.json"))
+            .expect("user_schema.json is a JSON object")
+    }
 }
-"#;
-
-pub const MODELS: &str = r#"use chrono::{DateTime, Utc};
-use mongodb::bson::oid::ObjectId;
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
 
+/// Refresh token document (only the token hash is stored)
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct UserDocument {
+pub struct RefreshTokenDocument {
     #[serde(rename = "_id")]
     pub id: ObjectId,
-    pub username: String,
-    pub email: String,
-    pub password: String,
-    #[serde(
-        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime",
-        rename = "createdAt"
-    )]
-    pub created_at: DateTime<Utc>,
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(rename = "familyId")]
+    pub family_id: String,
+    #[serde(rename = "tokenHash")]
+    pub token_hash: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: BsonDateTime,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<BsonDateTime>,
+    #[serde(rename = "createdAt")]
+    pub created_at: BsonDateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
@@ -189,6 +175,15 @@ pub struct LoginCredentials {
     pub password: String,
 }
 
+impl Validate for LoginCredentials {
+    fn validate(&self) -> Result<(), FieldErrors> {
+        FieldErrors::default()
+            .check("email", &self.email, &[Rule::Length { min: 3, max: 254 }, Rule::Email])
+            .check("password", &self.password, &[Rule::Length { min: 1, max: 128 }])
+            .into_result()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct RegistrationCredentials {
     pub username: String,
@@ -196,74 +191,135 @@ pub struct RegistrationCredentials {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Validate for RegistrationCredentials {
+    fn validate(&self) -> Result<(), FieldErrors> {
+        FieldErrors::default()
+            .check(
+                "username",
+                &self.username,
+                &[
+                    Rule::Length { min: 3, max: 32 },
+                    Rule::Matches {
+                        pattern: &USERNAME,
+                        description: "only contain letters, digits, '.', '_' and '-'",
+                    },
+                ],
+            )
+            .check("email", &self.email, &[Rule::Length { min: 3, max: 254 }, Rule::Email])
+            .check("password", &self.password, &[Rule::Length { min: 8, max: 128 }])
+            .into_result()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct SuccessResponse {
     pub status: u16,
     pub message: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct ErrorResponse {
+/// Tokens returned in the body for clients that cannot rely on cookies
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct TokenResponse {
+    pub token_type: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct LoginResponse {
     pub status: u16,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<TokenResponse>,
 }
-"#;
 
-pub const OPTIONS: &str = r#"
-#[rocket::options("/<_route_args..>")]
-pub async fn options(_route_args: Option<std::path::PathBuf>) -> rocket::http::Status {
-    rocket::http::Status::Ok
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn json_schema_matches_user_document() {
+        let user = UserDocument {
+            id: ObjectId::new(),
+            username: "jane".to_string(),
+            email: "jane@example.com".to_string(),
+            password: "hash".to_string(),
+            roles: default_roles(),
+            created_at: Utc::now(),
+        };
+        let document = bson::to_document(&user).unwrap();
+        let schema = UserDocument::json_schema();
+        let properties = schema.get_document("properties").unwrap();
+
+        let fields: BTreeSet<&String> = document.keys().collect();
+        let described: BTreeSet<&String> = properties.keys().collect();
+        assert_eq!(fields, described);
+
+        for field in schema.get_array("required").unwrap() {
+            assert!(document.contains_key(field.as_str().unwrap()));
+        }
+    }
 }
 "#;
 
-pub const ROUTES_MOD: &str = r#"use crate::auth::{authorize_user, hash_password};
-use crate::guards::AuthClaims;
-use crate::models::{ErrorResponse, SuccessResponse, UserInfo};
+pub const USER_SCHEMA: &str = r#"{{{cli.user_schema}}}
+"#;
+
+pub const ROUTES_MOD: &str = r#"use crate::auth::{
+    AuthConfig, authorize_user, hash_password, hash_refresh_token, issue_token, needs_rehash,
+    new_refresh_token, remove_auth_cookies, set_auth_cookies, token_response,
+};
+use crate::config::AppConfig;
+use crate::errors::ApiError;
+use crate::guards::{Admin, AuthClaims, RequireRole};
+use crate::models::{LoginResponse, RefreshRequest, SuccessResponse, UserInfo};
 use crate::models::{LoginCredentials, RegistrationCredentials, User, UserDocument};
-use crate::repositories::UserRepository;
+use crate::pagination::{Page, Pagination};
+use crate::repositories::{StoreError, UserQuery, UserStore, USER_SORT_FIELDS};
+use crate::validation::Validated;
 
+use rocket::http::CookieJar;
 use rocket::http::Status;
-use rocket::http::{Cookie, CookieJar, SameSite};
 use rocket::serde::json::Json;
 use rocket::{State, delete, get, post, put, routes};
 
+use chrono::Utc;
+use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 
 /// Registers a new user.
 #[post("/register", data = "<credentials>")]
 pub async fn register(
-    repo: &State<Arc<UserRepository>>,
-    credentials: Json<RegistrationCredentials>,
-) -> Result<Json<SuccessResponse>, Json<ErrorResponse>> {
-    if let Ok(Some(_)) = repo.get_user_by_email(&credentials.email).await {
-        return Err(Json(ErrorResponse {
-            status: Status::Conflict.code,
-            message: "A user with this email already exists".to_string(),
-        }));
+    repo: &State<Arc<dyn UserStore>>,
+    auth_config: &State<AuthConfig>,
+    config: &State<AppConfig>,
+    credentials: Validated<Json<RegistrationCredentials>>,
+) -> Result<Json<SuccessResponse>, ApiError> {
+    if !config.features.registration {
+        return Err(ApiError::new(Status::Forbidden, "Registration is disabled"));
     }
 
-    let hashed_password = match hash_password(credentials.password.clone()) {
+    let hashed_password = match hash_password(&credentials.password, auth_config) {
         Ok(hash) => hash,
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
-        }
+        Err(_) => return Err(ApiError::internal()),
     };
 
-    let _ = match repo
-        .create_user(&credentials.username, &credentials.email, &hashed_password)
+    repo.create_user(&credentials.username, &credentials.email, &hashed_password)
         .await
-    {
-        Ok(user) => user,
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Failed to register account".to_string(),
-            }));
-        }
-    };
+        .map_err(|e| match e {
+            // The unique email index rejects duplicates, even under concurrent requests.
+            StoreError::Conflict => {
+                ApiError::new(Status::Conflict, "A user with this email already exists")
+            }
+            _ => ApiError::new(Status::InternalServerError, "Failed to register account"),
+        })?;
 
     Ok(Json(SuccessResponse {
         status: Status::Ok.code,
@@ -271,27 +327,22 @@ pub async fn register(
     }))
 }
 
-/// Authenticates a user and sets an authentication cookie.
-#[post("/login", data = "<credentials>")]
+/// Authenticates a user and sets the authentication cookies.
+///
+/// Clients that cannot use cookies (mobile apps, other services) can pass
+/// `?include_token=true` to also receive the tokens in the response body.
+#[post("/login?<include_token>", data = "<credentials>")]
 pub async fn login(
-    repo: &State<Arc<UserRepository>>,
-    credentials: Json<LoginCredentials>,
+    repo: &State<Arc<dyn UserStore>>,
+    credentials: Validated<Json<LoginCredentials>>,
+    include_token: Option<bool>,
+    auth_config: &State<AuthConfig>,
     cookies: &CookieJar<'_>,
-) -> Result<Json<SuccessResponse>, Json<ErrorResponse>> {
+) -> Result<Json<LoginResponse>, ApiError> {
     let user_document = match repo.get_user_by_email(&credentials.email).await {
         Ok(Some(user_document)) => user_document,
-        Ok(None) => {
-            return Err(Json(ErrorResponse {
-                status: Status::Unauthorized.code,
-                message: "Invalid email or password".to_string(),
-            }));
-        }
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
-        }
+        Ok(None) => return Err(ApiError::new(Status::Unauthorized, "Invalid email or password")),
+        Err(_) => return Err(ApiError::internal()),
     };
 
     let user = User {
@@ -302,64 +353,211 @@ pub async fn login(
         created_at: user_document.created_at.to_rfc3339(),
     };
 
-    let token = match authorize_user(&user, &credentials).await {
+    if authorize_user(&user, &credentials).await.is_err() {
+        return Err(ApiError::new(Status::Unauthorized, "Invalid email or password"));
+    }
+
+    // Upgrade legacy bcrypt hashes (or outdated Argon2 parameters) now that
+    // the plaintext password is known. A failure here must not block login.
+    if needs_rehash(&user.password, auth_config) {
+        match hash_password(&credentials.password, auth_config) {
+            Ok(hash) => {
+                if let Err(e) = repo.update_user(&user.id, None, None, Some(&hash)).await {
+                    warn!("Failed to upgrade password hash for {}: {}", user.email, e);
+                }
+            }
+            Err(e) => warn!("Failed to upgrade password hash for {}: {}", user.email, e),
+        }
+    }
+
+    // Every login starts a new session (a family of rotating refresh tokens).
+    let session_id = ObjectId::new().to_hex();
+    let refresh_token = new_refresh_token(auth_config);
+
+    if repo
+        .create_refresh_token(
+            &user.id,
+            &session_id,
+            &refresh_token.hash,
+            refresh_token.expires_at,
+        )
+        .await
+        .is_err()
+    {
+        return Err(ApiError::internal());
+    }
+
+    let token = match issue_token(&user.email, &session_id, &user_document.roles, auth_config) {
         Ok(token) => token,
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::Unauthorized.code,
-                message: "Invalid email or password".to_string(),
-            }));
+        Err(_) => return Err(ApiError::internal()),
+    };
+
+    set_auth_cookies(cookies, &token, &refresh_token, auth_config);
+
+    Ok(Json(LoginResponse {
+        status: Status::Ok.code,
+        message: "Login successful".to_string(),
+        tokens: include_token
+            .unwrap_or(false)
+            .then(|| token_response(token, &refresh_token, auth_config)),
+    }))
+}
+
+/// Exchanges a refresh token for a new access token, rotating the refresh token.
+///
+/// The refresh token is read from the JSON body when present (and the new
+/// tokens are then returned in the body), otherwise from the cookie.
+#[post("/refresh", data = "<body>")]
+pub async fn refresh(
+    repo: &State<Arc<dyn UserStore>>,
+    body: Option<Json<RefreshRequest>>,
+    auth_config: &State<AuthConfig>,
+    cookies: &CookieJar<'_>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    let from_body = body.is_some();
+    let presented = match body {
+        Some(body) => Some(body.into_inner().refresh_token),
+        None => cookies.get("refresh_token").map(|c| c.value().to_string()),
+    };
+
+    let token_hash = match presented {
+        Some(token) => hash_refresh_token(&token),
+        None => return Err(ApiError::new(Status::Unauthorized, "Missing refresh token")),
+    };
+
+    let current = match repo.consume_refresh_token(&token_hash).await {
+        Ok(Some(token)) => token,
+        Ok(None) => {
+            // A known but already rotated token is being replayed: end the whole session.
+            if let Ok(Some(reused)) = repo.get_refresh_token(&token_hash).await {
+                let _ = repo.revoke_session(&reused.family_id).await;
+            }
+            remove_auth_cookies(cookies);
+            return Err(ApiError::new(Status::Unauthorized, "Invalid refresh token"));
         }
+        Err(_) => return Err(ApiError::internal()),
     };
 
-    // Set the token cookie (HTTP-only, secure)
-    #[allow(deprecated)]
-    let cookie = Cookie::build(("auth_token", token.clone()))
-        .http_only(true)
-        .secure(false) // Ensure your app uses HTTPS
-        .same_site(SameSite::Lax)
-        .path("/")
-        .finish();
+    if current.expires_at.to_chrono() <= Utc::now() {
+        remove_auth_cookies(cookies);
+        return Err(ApiError::new(Status::Unauthorized, "Refresh token expired"));
+    }
 
-    cookies.add(cookie);
+    let user = match repo.get_user_by_id(&current.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            remove_auth_cookies(cookies);
+            return Err(ApiError::new(Status::Unauthorized, "Invalid refresh token"));
+        }
+        Err(_) => return Err(ApiError::internal()),
+    };
 
-    Ok(Json(SuccessResponse {
+    let next = new_refresh_token(auth_config);
+
+    if repo
+        .create_refresh_token(
+            &current.user_id,
+            &current.family_id,
+            &next.hash,
+            next.expires_at,
+        )
+        .await
+        .is_err()
+    {
+        return Err(ApiError::internal());
+    }
+
+    let token = match issue_token(&user.email, &current.family_id, &user.roles, auth_config) {
+        Ok(token) => token,
+        Err(_) => return Err(ApiError::internal()),
+    };
+
+    set_auth_cookies(cookies, &token, &next, auth_config);
+
+    Ok(Json(LoginResponse {
         status: Status::Ok.code,
-        message: "Login successful".to_string(),
+        message: "Token refreshed".to_string(),
+        tokens: from_body.then(|| token_response(token, &next, auth_config)),
     }))
 }
 
-/// Logs out the current user by removing the authentication cookie.
+/// Logs out the current user, revoking the session server-side and removing its cookies.
+///
+/// The session is identified by the refresh token cookie or, for bearer
+/// clients, by the access token presented with the request.
 #[post("/logout")]
-pub fn logout(cookies: &CookieJar<'_>) -> Json<SuccessResponse> {
-    cookies.remove(Cookie::build(("auth_token", "")).path("/").build());
+pub async fn logout(
+    claims: Option<AuthClaims>,
+    repo: &State<Arc<dyn UserStore>>,
+    cookies: &CookieJar<'_>,
+) -> Json<SuccessResponse> {
+    if let Some(claims) = claims {
+        let _ = repo.revoke_session(&claims.session_id).await;
+    }
+    if let Some(cookie) = cookies.get("refresh_token") {
+        let token_hash = hash_refresh_token(cookie.value());
+        if let Ok(Some(token)) = repo.get_refresh_token(&token_hash).await {
+            let _ = repo.revoke_session(&token.family_id).await;
+        }
+    }
+
+    remove_auth_cookies(cookies);
     Json(SuccessResponse {
         status: 200,
         message: "Logged out successfully".to_string(),
     })
 }
 
+/// Lists users a page at a time (requires the admin role).
+///
+/// `sort` takes `username`, `email` or `created_at`, prefixed with `-` for
+/// descending order, and `q` matches part of the username or email.
+#[get("/users?<pagination..>")]
+pub async fn list_users(
+    _admin: RequireRole<Admin>,
+    repo: &State<Arc<dyn UserStore>>,
+    pagination: Pagination,
+) -> Result<Json<Page<UserInfo>>, ApiError> {
+    let sort = match pagination.sort(USER_SORT_FIELDS) {
+        Ok(sort) => sort,
+        Err(message) => return Err(ApiError::new(Status::BadRequest, message)),
+    };
+
+    let query = UserQuery {
+        search: pagination.search(),
+        sort,
+        offset: pagination.offset(),
+        limit: pagination.per_page,
+    };
+    let (users, total) = match repo.list_users(&query).await {
+        Ok(result) => result,
+        Err(_) => return Err(ApiError::internal()),
+    };
+
+    let users = users
+        .into_iter()
+        .map(|user| UserInfo {
+            id: user.id.to_string(),
+            username: user.username,
+            email: user.email,
+            created_at: user.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(Json(Page::new(users, total, &pagination, "/users")))
+}
+
 /// Retrieves a single user by ID (requires authentication).
 #[get("/users/<id>")]
 pub async fn get_user(
     _auth: AuthClaims,
-    repo: &State<Arc<UserRepository>>,
+    repo: &State<Arc<dyn UserStore>>,
     id: &str,
-) -> Result<Json<UserDocument>, Json<ErrorResponse>> {
-    let user = match repo.get_user_by_id(&id).await {
+) -> Result<Json<UserDocument>, ApiError> {
+    let user = match repo.get_user_by_id(id).await {
         Ok(Some(user)) => user,
-        Ok(None) => {
-            return Err(Json(ErrorResponse {
-                status: Status::NotFound.code,
-                message: "User not found".to_string(),
-            }));
-        }
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
-        }
+        Ok(None) => return Err(ApiError::new(Status::NotFound, "User not found")),
+        Err(_) => return Err(ApiError::internal()),
     };
 
     Ok(Json(user))
@@ -369,23 +567,13 @@ pub async fn get_user(
 #[get("/user/<email>")]
 pub async fn get_user_by_email(
     _auth: AuthClaims,
-    repo: &State<Arc<UserRepository>>,
+    repo: &State<Arc<dyn UserStore>>,
     email: &str,
-) -> Result<Json<UserInfo>, Json<ErrorResponse>> {
-    let user = match repo.get_user_by_email(&email).await {
+) -> Result<Json<UserInfo>, ApiError> {
+    let user = match repo.get_user_by_email(email).await {
         Ok(Some(user)) => user,
-        Ok(None) => {
-            return Err(Json(ErrorResponse {
-                status: Status::NotFound.code,
-                message: "User not found".to_string(),
-            }));
-        }
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
-        }
+        Ok(None) => return Err(ApiError::new(Status::NotFound, "User not found")),
+        Err(_) => return Err(ApiError::internal()),
     };
 
     Ok(Json(UserInfo {
@@ -396,38 +584,25 @@ pub async fn get_user_by_email(
     }))
 }
 
-/// Updates an existing user's information by ID (requires authentication).
+/// Updates an existing user's information by ID (requires authentication as that user or an admin).
 #[put("/update/<id>", data = "<credentials>")]
 pub async fn update_user(
-    _auth: AuthClaims,
-    repo: &State<Arc<UserRepository>>,
+    auth: AuthClaims,
+    repo: &State<Arc<dyn UserStore>>,
+    auth_config: &State<AuthConfig>,
     id: &str,
-    credentials: Json<RegistrationCredentials>,
-) -> Result<Json<UserDocument>, Json<ErrorResponse>> {
-    // Check if the email is already in use by another user
-    if let Ok(Some(existing_user)) = repo.get_user_by_email(&credentials.email).await {
-        // If the email exists and it's not the user being updated
-        if existing_user.id.to_string() != id {
-            return Err(Json(ErrorResponse {
-                status: Status::Conflict.code,
-                message: "A user with this email already exists".to_string(),
-            }));
-        }
-    }
+    credentials: Validated<Json<RegistrationCredentials>>,
+) -> Result<Json<UserDocument>, ApiError> {
+    authorize_owner(&auth, repo.as_ref(), id).await?;
 
-    let hashed_password = match hash_password(credentials.password.clone()) {
+    let hashed_password = match hash_password(&credentials.password, auth_config) {
         Ok(hash) => hash,
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
-        }
+        Err(_) => return Err(ApiError::internal()),
     };
 
     let user = match repo
         .update_user(
-            &id,
+            id,
             Some(&credentials.username),
             Some(&credentials.email),
             Some(&hashed_password),
@@ -435,47 +610,55 @@ pub async fn update_user(
         .await
     {
         Ok(Some(user)) => user,
-        Ok(None) => {
-            return Err(Json(ErrorResponse {
-                status: Status::NotFound.code,
-                message: "User not found".to_string(),
-            }));
-        }
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
+        Ok(None) => return Err(ApiError::new(Status::NotFound, "User not found")),
+        // Another user already has this email.
+        Err(StoreError::Conflict) => {
+            return Err(ApiError::new(
+                Status::Conflict,
+                "A user with this email already exists",
+            ));
         }
+        Err(_) => return Err(ApiError::internal()),
     };
 
+    // The password was replaced, so every existing session must log in again.
+    if repo.revoke_user_sessions(id).await.is_err() {
+        return Err(ApiError::internal());
+    }
+
     Ok(Json(user))
 }
 
-/// Deletes a user by ID (requires authentication).
+/// Deletes a user by ID (requires authentication as that user or an admin).
 #[delete("/delete/<id>")]
 pub async fn delete_user(
-    _auth: AuthClaims,
-    repo: &State<Arc<UserRepository>>,
+    auth: AuthClaims,
+    repo: &State<Arc<dyn UserStore>>,
     id: &str,
-) -> Result<Json<SuccessResponse>, Json<ErrorResponse>> {
-    match repo.delete_user(&id).await {
+) -> Result<Json<SuccessResponse>, ApiError> {
+    authorize_owner(&auth, repo.as_ref(), id).await?;
+
+    match repo.delete_user(id).await {
         Ok(Some(_)) => Ok(Json(SuccessResponse {
             status: Status::Ok.code,
             message: "User deleted successfully".to_string(),
         })),
-        Ok(None) => {
-            return Err(Json(ErrorResponse {
-                status: Status::NotFound.code,
-                message: "User not found".to_string(),
-            }));
-        }
-        Err(_) => {
-            return Err(Json(ErrorResponse {
-                status: Status::InternalServerError.code,
-                message: "Something went wrong, please try again later".to_string(),
-            }));
-        }
+        Ok(None) => Err(ApiError::new(Status::NotFound, "User not found")),
+        Err(_) => Err(ApiError::internal()),
+    }
+}
+
+/// Ensures the caller is the user identified by `id` or an admin.
+async fn authorize_owner(
+    auth: &AuthClaims,
+    repo: &dyn UserStore,
+    id: &str,
+) -> Result<(), ApiError> {
+    match repo.get_user_by_id(id).await {
+        Ok(Some(user)) if auth.is_self_or_admin(&user.email) => Ok(()),
+        Ok(Some(_)) => Err(ApiError::new(Status::Forbidden, "You can only modify your own account")),
+        Ok(None) => Err(ApiError::new(Status::NotFound, "User not found")),
+        Err(_) => Err(ApiError::internal()),
     }
 }
 
@@ -484,49 +667,341 @@ pub fn user_routes() -> Vec<rocket::Route> {
     routes![
         register,
         login,
+        refresh,
         logout,
+        list_users,
         get_user,
         get_user_by_email,
         update_user,
         delete_user
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Argon2Config;
+    use crate::models::TokenResponse;
+    use crate::repositories::memory::InMemoryUserStore;
+    use rocket::http::{ContentType, Header};
+    use rocket::local::asynchronous::Client;
+    use rocket::serde::json::Value;
+
+    async fn client(store: Arc<dyn UserStore>) -> Client {
+        let auth_config = AuthConfig {
+            issuer: "{{project_name}}".to_string(),
+            audience: "{{project_name}}-api".to_string(),
+            // Cheap parameters keep the tests fast.
+            argon2: Argon2Config {
+                memory_kib: 1024,
+                iterations: 1,
+                parallelism: 1,
+            },
+            secret: "test-secret".to_string(),
+            ..AuthConfig::default()
+        };
+        let rocket = rocket::build()
+            .manage(store)
+            .manage(auth_config)
+            .manage(AppConfig::default())
+            .attach(crate::fairings::RequestId)
+            .register("/", crate::catchers::all())
+            .mount("/", user_routes());
+        Client::untracked(rocket).await.unwrap()
+    }
+
+    async fn register(client: &Client, email: &str) -> u16 {
+        let credentials = RegistrationCredentials {
+            username: "jane".to_string(),
+            email: email.to_string(),
+            password: "correct horse".to_string(),
+        };
+        let response = client.post("/register").json(&credentials).dispatch().await;
+        response.status().code
+    }
+
+    async fn login(client: &Client, email: &str) -> TokenResponse {
+        let credentials = LoginCredentials {
+            email: email.to_string(),
+            password: "correct horse".to_string(),
+        };
+        let response = client
+            .post("/login?include_token=true")
+            .json(&credentials)
+            .dispatch()
+            .await;
+        response.into_json::<LoginResponse>().await.unwrap().tokens.unwrap()
+    }
+
+    async fn refresh(client: &Client, refresh_token: &str) -> Result<TokenResponse, Status> {
+        let body = RefreshRequest {
+            refresh_token: refresh_token.to_string(),
+        };
+        let response = client.post("/refresh").json(&body).dispatch().await;
+        if response.status() != Status::Ok {
+            return Err(response.status());
+        }
+        Ok(response.into_json::<LoginResponse>().await.unwrap().tokens.unwrap())
+    }
+
+    #[rocket::async_test]
+    async fn registered_users_can_log_in_and_read_their_account() {
+        let store = Arc::new(InMemoryUserStore::default());
+        let client = client(store.clone()).await;
+
+        assert_eq!(register(&client, "jane@example.com").await, 200);
+        assert_eq!(register(&client, "jane@example.com").await, 409);
+
+        let tokens = login(&client, "jane@example.com").await;
+        let user = store.get_user_by_email("jane@example.com").await.unwrap().unwrap();
+        let response = client
+            .get(format!("/users/{}", user.id))
+            .header(Header::new(
+                "Authorization",
+                format!("Bearer {}", tokens.access_token),
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[rocket::async_test]
+    async fn invalid_registrations_are_rejected_per_field() {
+        let client = client(Arc::new(InMemoryUserStore::default())).await;
+        let credentials = RegistrationCredentials {
+            username: "j".to_string(),
+            email: "not-an-email".to_string(),
+            password: "short".to_string(),
+        };
+        let response = client.post("/register").json(&credentials).dispatch().await;
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let body: Value = response.into_json().await.unwrap();
+        assert_eq!(body["status"], 422);
+        assert_eq!(body["detail"], "Validation failed.");
+        assert_eq!(
+            body["errors"]["username"][0],
+            "must be between 3 and 32 characters"
+        );
+        assert_eq!(body["errors"]["email"][0], "must be a valid email address");
+        assert_eq!(
+            body["errors"]["password"][0],
+            "must be between 8 and 128 characters"
+        );
+    }
+
+    #[rocket::async_test]
+    async fn errors_are_problem_documents() {
+        let client = client(Arc::new(InMemoryUserStore::default())).await;
+        let response = client
+            .get("/users")
+            .header(Header::new("X-Request-Id", "test-123"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "problem+json"))
+        );
+        assert_eq!(response.headers().get_one("X-Request-Id"), Some("test-123"));
+        let body: Value = response.into_json().await.unwrap();
+        assert_eq!(body["title"], "Unauthorized");
+        assert_eq!(body["status"], 401);
+        assert_eq!(body["instance"], "/users");
+        assert_eq!(body["request_id"], "test-123");
+    }
+
+    #[rocket::async_test]
+    async fn reusing_a_refresh_token_revokes_the_session() {
+        let client = client(Arc::new(InMemoryUserStore::default())).await;
+        register(&client, "jane@example.com").await;
+        let tokens = login(&client, "jane@example.com").await;
+
+        let rotated = refresh(&client, &tokens.refresh_token).await.unwrap();
+        assert_ne!(rotated.refresh_token, tokens.refresh_token);
+
+        // Replaying the first token ends the session, so the rotated one stops working too.
+        assert_eq!(
+            refresh(&client, &tokens.refresh_token).await.unwrap_err(),
+            Status::Unauthorized
+        );
+        assert_eq!(
+            refresh(&client, &rotated.refresh_token).await.unwrap_err(),
+            Status::Unauthorized
+        );
+    }
+
+    #[rocket::async_test]
+    async fn admins_can_list_users_a_page_at_a_time() {
+        let store = Arc::new(InMemoryUserStore::default());
+        let client = client(store.clone()).await;
+        for email in ["ann@example.com", "bob@example.com", "cat@example.com"] {
+            register(&client, email).await;
+        }
+        let bearer = |tokens: &TokenResponse| {
+            Header::new("Authorization", format!("Bearer {}", tokens.access_token))
+        };
+
+        let tokens = login(&client, "ann@example.com").await;
+        let response = client.get("/users").header(bearer(&tokens)).dispatch().await;
+        assert_eq!(response.status(), Status::Forbidden);
+
+        // Roles are read at login.
+        store.add_role("ann@example.com", "admin");
+        let tokens = login(&client, "ann@example.com").await;
+        let response = client
+            .get("/users?per_page=2&sort=-email")
+            .header(bearer(&tokens))
+            .dispatch()
+            .await;
+        let page: Value = response.into_json().await.unwrap();
+
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["items"][0]["email"], "cat@example.com");
+        assert_eq!(page["items"][1]["email"], "bob@example.com");
+        assert_eq!(page["links"]["next"], "/users?page=2&per_page=2&sort=-email");
+
+        let response = client
+            .get("/users?q=BOB")
+            .header(bearer(&tokens))
+            .dispatch()
+            .await;
+        let page: Value = response.into_json().await.unwrap();
+        assert_eq!(page["total"], 1);
+    }
+}
 "#;
 
-pub const DB: &str = r#"use dotenvy::dotenv;
-use mongodb::{Client, options::ClientOptions};
+pub const DB: &str = r#"use mongodb::bson::{doc, Document};
+use mongodb::options::{ClientOptions, IndexOptions};
+use mongodb::{Client, Database, IndexModel};
 use rocket::fairing::AdHoc;
+use rocket::tokio::time::{Instant, sleep};
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::repositories::UserRepository;
-
+use crate::config::{AppConfig, DatabaseConfig};
+use crate::models::UserDocument;
+use crate::repositories::{UserRepository, UserStore};
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Collection, index name, indexed field and uniqueness of every index the
+/// repository relies on. `rocket-cli db indexes` creates the same set.
+const INDEXES: &[(&str, &str, &str, bool)] = &[
+    ("users", "users_email_unique", "email", true),
+    ("users", "users_username", "username", false),
+    ("refresh_tokens", "refresh_tokens_token_hash_unique", "tokenHash", true),
+    ("refresh_tokens", "refresh_tokens_family_id", "familyId", false),
+    ("refresh_tokens", "refresh_tokens_user_id", "userId", false),
+];
+
+/// Connects to MongoDB, retrying with exponential backoff for
+/// `database.retry_for_secs` before aborting ignition.
+///
+/// Once connected, the indexes in [`INDEXES`] are created and, with
+/// `database.validate_schema`, the `users` schema validator is applied.
 pub fn init() -> AdHoc {
-    AdHoc::on_ignite(
+    AdHoc::try_on_ignite(
         "Establish connection with Database cluster",
         |rocket| async {
-            match connect().await {
-                Ok(user_repository) => rocket.manage(user_repository),
-                Err(error) => {
-                    panic!("Cannot connect to instance -> {:?}", error)
+            let Some(config) = rocket.state::<AppConfig>().map(|c| c.database.clone()) else {
+                error!("AppConfig::fairing() must be attached before db::init()");
+                return Err(rocket);
+            };
+
+            let deadline = Instant::now() + Duration::from_secs(config.retry_for_secs);
+            let mut delay = Duration::from_millis(250);
+
+            loop {
+                match connect(&config).await {
+                    Ok(client) => {
+                        let database = client.database(&config.name);
+                        if let Err(e) = sync_schema(&database, config.validate_schema).await {
+                            error!("Cannot prepare database indexes: {}", e);
+                            return Err(rocket);
+                        }
+
+                        let store: Arc<dyn UserStore> =
+                            Arc::new(UserRepository::new(&client, &config.name, "users"));
+                        return Ok(rocket.manage(store));
+                    }
+                    Err(e) if Instant::now() + delay < deadline => {
+                        warn!("Database unavailable ({}), retrying in {:?}", e, delay);
+                        sleep(delay).await;
+                        delay = (delay * 2).min(MAX_RETRY_DELAY);
+                    }
+                    Err(e) => {
+                        error!(
+                            "Cannot connect to database after {}s: {}",
+                            config.retry_for_secs, e
+                        );
+                        return Err(rocket);
+                    }
                 }
             }
         },
     )
 }
 
-async fn connect() -> mongodb::error::Result<Arc<UserRepository>> {
-    dotenv().ok();
-    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set...");
-    let database_name = std::env::var("DATABASE").expect("DATABASE must be set...");
-    let client_options = ClientOptions::parse(database_url).await?;
+async fn connect(config: &DatabaseConfig) -> mongodb::error::Result<Client> {
+    let timeout = Duration::from_secs(config.connect_timeout_secs);
+
+    let mut client_options = ClientOptions::parse(&config.url).await?;
+    client_options.max_pool_size = Some(config.max_connections);
+    client_options.connect_timeout = Some(timeout);
+    client_options.server_selection_timeout = Some(timeout);
+
     let client = Client::with_options(client_options)?;
-    let _database = client.database(&database_name);
 
-    Ok(Arc::new(UserRepository::new(
-        &client,
-        &database_name,
-        "users",
-    )))
+    // The driver connects lazily; make sure the cluster is actually reachable.
+    client
+        .database(&config.name)
+        .run_command(doc! { "ping": 1 })
+        .await?;
+
+    Ok(client)
+}
+
+/// Creates missing indexes (existing identical ones are left alone) and,
+/// when `validate` is set, installs the `UserDocument` validator.
+async fn sync_schema(database: &Database, validate: bool) -> mongodb::error::Result<()> {
+    for (collection, name, field, unique) in INDEXES {
+        let options = IndexOptions::builder()
+            .name(name.to_string())
+            .unique(*unique)
+            .build();
+        let index = IndexModel::builder()
+            .keys(doc! { *field: 1 })
+            .options(options)
+            .build();
+        database
+            .collection::<Document>(collection)
+            .create_index(index)
+            .await?;
+    }
+
+    if validate {
+        let exists = database
+            .list_collection_names()
+            .await?
+            .iter()
+            .any(|name| name == "users");
+        let command = if exists { "collMod" } else { "create" };
+
+        // `moderate` leaves documents that predate the validator untouched.
+        database
+            .run_command(doc! {
+                command: "users",
+                "validator": { "$jsonSchema": UserDocument::json_schema() },
+                "validationLevel": "moderate",
+            })
+            .await?;
+        info!("Schema validation enabled on users");
+    }
+
+    Ok(())
 }
 "#;
 
@@ -534,47 +1009,183 @@ pub const REPOSITORIES: &str = r#"#![allow(unused)]
 use chrono::{DateTime, Utc};
 use futures::stream::TryStreamExt;
 use mongodb::{
-    bson::{doc, oid::ObjectId},
-    error::{Error, Result},
-    options::ClientOptions,
-    Client, Collection,
+    bson::{doc, oid::ObjectId, DateTime as BsonDateTime},
+    error::{Error, ErrorKind, WriteFailure},
+    options::{ClientOptions, ReturnDocument},
+    Client, Collection, Database,
 };
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::{RefreshTokenDocument, UserDocument, DEFAULT_ROLE};
+use crate::pagination::Sort;
+
+#[cfg(test)]
+pub mod memory;
+
+/// Fields `GET /users` can be sorted by.
+pub const USER_SORT_FIELDS: &[&str] = &["username", "email", "created_at"];
+
+/// Which users [`UserStore::list_users`] returns.
+#[derive(Debug, Clone)]
+pub struct UserQuery<'a> {
+    /// Case-insensitive part of the username or email.
+    pub search: Option<&'a str>,
+    /// Defaults to `created_at`, oldest first.
+    pub sort: Option<Sort>,
+    pub offset: u64,
+    pub limit: u64,
+}
 
-use crate::models::UserDocument;
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Errors returned by a [`UserStore`].
+#[derive(Debug)]
+pub enum StoreError {
+    /// Another user already has this email.
+    Conflict,
+    /// The backend failed, e.g. the database is unreachable.
+    Backend(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Conflict => write!(f, "a user with this email already exists"),
+            StoreError::Backend(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<Error> for StoreError {
+    fn from(error: Error) -> Self {
+        if is_duplicate_key(&error) {
+            StoreError::Conflict
+        } else {
+            StoreError::Backend(error.to_string())
+        }
+    }
+}
+
+/// Whether `error` is a unique index violation (E11000).
+fn is_duplicate_key(error: &Error) -> bool {
+    const DUPLICATE_KEY: i32 = 11000;
+
+    match error.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(e)) => e.code == DUPLICATE_KEY,
+        ErrorKind::Command(e) => e.code == DUPLICATE_KEY,
+        _ => false,
+    }
+}
+
+/// Storage for users and their sessions, managed as `Arc<dyn UserStore>`.
+///
+/// Routes and guards only depend on this trait, so the database can be
+/// swapped, and tests can use [`memory::InMemoryUserStore`] instead.
+/// Ids are passed as strings; ids that cannot exist (e.g. malformed
+/// ObjectIds) simply match nothing.
+#[rocket::async_trait]
+pub trait UserStore: Send + Sync {
+    /// Creates a user with the default role. Fails with [`StoreError::Conflict`] when the email is taken.
+    async fn create_user(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> StoreResult<UserDocument>;
 
+    async fn get_user_by_id(&self, id: &str) -> StoreResult<Option<UserDocument>>;
+
+    async fn get_user_by_email(&self, email: &str) -> StoreResult<Option<UserDocument>>;
+
+    /// Updates the given fields and returns the updated user. Fails with
+    /// [`StoreError::Conflict`] when the email belongs to another user.
+    async fn update_user(
+        &self,
+        id: &str,
+        username: Option<&str>,
+        email: Option<&str>,
+        password: Option<&str>,
+    ) -> StoreResult<Option<UserDocument>>;
+
+    /// Deletes a user, returning it.
+    async fn delete_user(&self, id: &str) -> StoreResult<Option<UserDocument>>;
+
+    /// Returns one page of matching users, and how many users match in total.
+    async fn list_users(&self, query: &UserQuery<'_>) -> StoreResult<(Vec<UserDocument>, u64)>;
+
+    /// Stores the hash of a new refresh token in the session `family_id`.
+    async fn create_refresh_token(
+        &self,
+        user_id: &str,
+        family_id: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> StoreResult<()>;
+
+    /// Gets a refresh token by hash, whether or not it has been revoked.
+    async fn get_refresh_token(&self, token_hash: &str)
+        -> StoreResult<Option<RefreshTokenDocument>>;
+
+    /// Revokes an unrevoked refresh token and returns it, atomically.
+    async fn consume_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> StoreResult<Option<RefreshTokenDocument>>;
+
+    /// Revokes every refresh token of a session.
+    async fn revoke_session(&self, family_id: &str) -> StoreResult<()>;
+
+    /// Revokes every session of a user.
+    async fn revoke_user_sessions(&self, user_id: &str) -> StoreResult<()>;
+
+    /// Whether a session still holds a usable refresh token.
+    async fn is_session_active(&self, family_id: &str) -> StoreResult<bool>;
+
+    /// Checks that the backend is reachable, for readiness probes.
+    async fn ping(&self) -> StoreResult<()>;
+}
+
+/// [`UserStore`] backed by MongoDB.
 #[derive(Debug)]
 pub struct UserRepository {
+    database: Database,
     collection: Collection<UserDocument>,
+    refresh_tokens: Collection<RefreshTokenDocument>,
 }
 
 impl UserRepository {
     pub fn new(client: &Client, db_name: &str, collection_name: &str) -> Self {
-        let collection = client
-            .database(db_name)
-            .collection::<UserDocument>(collection_name);
-        Self { collection }
+        let database = client.database(db_name);
+        let collection = database.collection::<UserDocument>(collection_name);
+        let refresh_tokens = database.collection::<RefreshTokenDocument>("refresh_tokens");
+        Self {
+            database,
+            collection,
+            refresh_tokens,
+        }
     }
+}
 
+#[rocket::async_trait]
+impl UserStore for UserRepository {
     /// CREATE a new user
-    pub async fn create_user(
+    ///
+    /// The unique email index rejects duplicates, which makes this safe under concurrency.
+    async fn create_user(
         &self,
         username: &str,
         email: &str,
         password: &str,
-    ) -> Result<UserDocument> {
-        if let Some(_) = self.collection.find_one(doc! { "email": email }).await? {
-            return Err(Error::from(std::io::Error::new(
-                std::io::ErrorKind::AlreadyExists,
-                "A user with this email already exists.",
-            )));
-        }
-
+    ) -> StoreResult<UserDocument> {
         let user = UserDocument {
             id: ObjectId::new(),
             username: username.to_string(),
             email: email.to_string(),
             password: password.to_string(),
+            roles: vec![DEFAULT_ROLE.to_string()],
             created_at: Utc::now(),
         };
 
@@ -584,7 +1195,7 @@ impl UserRepository {
     }
 
     /// GET user by id
-    pub async fn get_user_by_id(&self, id: &str) -> Result<Option<UserDocument>> {
+    async fn get_user_by_id(&self, id: &str) -> StoreResult<Option<UserDocument>> {
         match ObjectId::parse_str(id) {
             Ok(object_id) => {
                 let filter = doc! { "_id": object_id };
@@ -596,20 +1207,20 @@ impl UserRepository {
     }
 
     /// GET user by email
-    pub async fn get_user_by_email(&self, email: &str) -> Result<Option<UserDocument>> {
+    async fn get_user_by_email(&self, email: &str) -> StoreResult<Option<UserDocument>> {
         let filter = doc! { "email": email };
         let user = self.collection.find_one(filter).await?;
         Ok(user)
     }
 
     /// UPDATE a user
-    pub async fn update_user(
+    async fn update_user(
         &self,
         id: &str,
         username: Option<&str>,
         email: Option<&str>,
         password: Option<&str>,
-    ) -> Result<Option<UserDocument>> {
+    ) -> StoreResult<Option<UserDocument>> {
         let object_id = match ObjectId::parse_str(id) {
             Ok(oid) => oid,
             Err(_) => return Ok(None),
@@ -636,12 +1247,16 @@ impl UserRepository {
         let filter = doc! { "_id": object_id };
         let update = doc! { "$set": update_doc };
 
-        let user = self.collection.find_one_and_update(filter, update).await?;
+        let user = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(ReturnDocument::After)
+            .await?;
         Ok(user)
     }
 
     /// DELETE a user
-    pub async fn delete_user(&self, id: &str) -> Result<Option<UserDocument>> {
+    async fn delete_user(&self, id: &str) -> StoreResult<Option<UserDocument>> {
         let object_id = match ObjectId::parse_str(id) {
             Ok(oid) => oid,
             Err(_) => return Ok(None),
@@ -652,17 +1267,341 @@ impl UserRepository {
         Ok(user)
     }
 
-    /// GET all users
-    pub async fn list_users(&self) -> Result<Vec<UserDocument>> {
-        let filter = doc! {};
-        let mut cursor = self.collection.find(filter).await?;
+    /// GET one page of users
+    async fn list_users(&self, query: &UserQuery<'_>) -> StoreResult<(Vec<UserDocument>, u64)> {
+        let filter = match query.search {
+            Some(search) => {
+                let pattern = regex::escape(search);
+                doc! {
+                    "$or": [
+                        { "username": { "$regex": &pattern, "$options": "i" } },
+                        { "email": { "$regex": &pattern, "$options": "i" } },
+                    ]
+                }
+            }
+            None => doc! {},
+        };
+        let total = self.collection.count_documents(filter.clone()).await?;
+
+        let (field, order) = match query.sort {
+            Some(sort) if sort.descending => (sort.field, -1),
+            Some(sort) => (sort.field, 1),
+            None => ("created_at", 1),
+        };
+        // The API uses snake_case names, the documents camelCase.
+        let field = if field == "created_at" { "createdAt" } else { field };
+
+        let mut cursor = self
+            .collection
+            .find(filter)
+            .sort(doc! { field: order, "_id": 1 })
+            .skip(query.offset)
+            .limit(query.limit as i64)
+            .await?;
         let mut users = Vec::new();
 
         while let Some(user) = cursor.try_next().await? {
             users.push(user);
         }
 
-        Ok(users)
+        Ok((users, total))
+    }
+
+    /// CREATE a refresh token for a session
+    async fn create_refresh_token(
+        &self,
+        user_id: &str,
+        family_id: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> StoreResult<()> {
+        let token = RefreshTokenDocument {
+            id: ObjectId::new(),
+            user_id: user_id.to_string(),
+            family_id: family_id.to_string(),
+            token_hash: token_hash.to_string(),
+            expires_at: BsonDateTime::from_chrono(expires_at),
+            revoked_at: None,
+            created_at: BsonDateTime::now(),
+        };
+
+        self.refresh_tokens.insert_one(&token).await?;
+
+        Ok(())
+    }
+
+    /// GET refresh token by hash, whether or not it has been revoked
+    async fn get_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> StoreResult<Option<RefreshTokenDocument>> {
+        let filter = doc! { "tokenHash": token_hash };
+        Ok(self.refresh_tokens.find_one(filter).await?)
+    }
+
+    /// REVOKE an unrevoked refresh token and return it, atomically
+    async fn consume_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> StoreResult<Option<RefreshTokenDocument>> {
+        let filter = doc! { "tokenHash": token_hash, "revokedAt": null };
+        let update = doc! { "$set": { "revokedAt": BsonDateTime::now() } };
+        Ok(self.refresh_tokens.find_one_and_update(filter, update).await?)
+    }
+
+    /// REVOKE every refresh token of a session
+    async fn revoke_session(&self, family_id: &str) -> StoreResult<()> {
+        let filter = doc! { "familyId": family_id, "revokedAt": null };
+        let update = doc! { "$set": { "revokedAt": BsonDateTime::now() } };
+        self.refresh_tokens.update_many(filter, update).await?;
+        Ok(())
+    }
+
+    /// REVOKE every session of a user
+    async fn revoke_user_sessions(&self, user_id: &str) -> StoreResult<()> {
+        let filter = doc! { "userId": user_id, "revokedAt": null };
+        let update = doc! { "$set": { "revokedAt": BsonDateTime::now() } };
+        self.refresh_tokens.update_many(filter, update).await?;
+        Ok(())
+    }
+
+    /// CHECK whether a session still holds a usable refresh token
+    async fn is_session_active(&self, family_id: &str) -> StoreResult<bool> {
+        let filter = doc! {
+            "familyId": family_id,
+            "revokedAt": null,
+            "expiresAt": { "$gt": BsonDateTime::now() },
+        };
+        Ok(self.refresh_tokens.count_documents(filter).await? > 0)
+    }
+
+    /// PING the database
+    async fn ping(&self) -> StoreResult<()> {
+        self.database.run_command(doc! { "ping": 1 }).await?;
+        Ok(())
+    }
+}
+"#;
+
+pub const MEMORY_STORE: &str = r#"use super::{StoreError, StoreResult, UserQuery, UserStore};
+use crate::models::{RefreshTokenDocument, UserDocument, DEFAULT_ROLE};
+use crate::pagination::Sort;
+use chrono::{DateTime, Utc};
+use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use std::sync::Mutex;
+
+/// [`UserStore`] kept in memory, for testing routes without a database.
+#[derive(Default)]
+pub struct InMemoryUserStore {
+    users: Mutex<Vec<UserDocument>>,
+    refresh_tokens: Mutex<Vec<RefreshTokenDocument>>,
+}
+
+impl InMemoryUserStore {
+    /// Gives the user with `email` an extra role, e.g. to test admin-only routes.
+    pub fn add_role(&self, email: &str, role: &str) {
+        for user in self.users.lock().unwrap().iter_mut() {
+            if user.email == email {
+                user.roles.push(role.to_string());
+            }
+        }
+    }
+    /// Revokes every refresh token matching `matches`.
+    fn revoke_where(&self, matches: impl Fn(&RefreshTokenDocument) -> bool) {
+        let now = BsonDateTime::now();
+        for token in self.refresh_tokens.lock().unwrap().iter_mut() {
+            if token.revoked_at.is_none() && matches(token) {
+                token.revoked_at = Some(now);
+            }
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl UserStore for InMemoryUserStore {
+    async fn create_user(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> StoreResult<UserDocument> {
+        let mut users = self.users.lock().unwrap();
+        if users.iter().any(|user| user.email == email) {
+            return Err(StoreError::Conflict);
+        }
+
+        let user = UserDocument {
+            id: ObjectId::new(),
+            username: username.to_string(),
+            email: email.to_string(),
+            password: password.to_string(),
+            roles: vec![DEFAULT_ROLE.to_string()],
+            created_at: Utc::now(),
+        };
+        users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn get_user_by_id(&self, id: &str) -> StoreResult<Option<UserDocument>> {
+        let users = self.users.lock().unwrap();
+        Ok(users.iter().find(|user| user.id.to_hex() == id).cloned())
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> StoreResult<Option<UserDocument>> {
+        let users = self.users.lock().unwrap();
+        Ok(users.iter().find(|user| user.email == email).cloned())
+    }
+
+    async fn update_user(
+        &self,
+        id: &str,
+        username: Option<&str>,
+        email: Option<&str>,
+        password: Option<&str>,
+    ) -> StoreResult<Option<UserDocument>> {
+        let mut users = self.users.lock().unwrap();
+        if let Some(email) = email {
+            if users
+                .iter()
+                .any(|user| user.email == email && user.id.to_hex() != id)
+            {
+                return Err(StoreError::Conflict);
+            }
+        }
+
+        let Some(user) = users.iter_mut().find(|user| user.id.to_hex() == id) else {
+            return Ok(None);
+        };
+        if let Some(username) = username {
+            user.username = username.to_string();
+        }
+        if let Some(email) = email {
+            user.email = email.to_string();
+        }
+        if let Some(password) = password {
+            user.password = password.to_string();
+        }
+        Ok(Some(user.clone()))
+    }
+
+    async fn delete_user(&self, id: &str) -> StoreResult<Option<UserDocument>> {
+        let mut users = self.users.lock().unwrap();
+        Ok(users
+            .iter()
+            .position(|user| user.id.to_hex() == id)
+            .map(|index| users.remove(index)))
+    }
+
+    async fn list_users(&self, query: &UserQuery<'_>) -> StoreResult<(Vec<UserDocument>, u64)> {
+        let search = query.search.map(str::to_lowercase);
+        let mut users: Vec<UserDocument> = self
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|user| match &search {
+                Some(search) => {
+                    user.username.to_lowercase().contains(search)
+                        || user.email.to_lowercase().contains(search)
+                }
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        let sort = query.sort.unwrap_or(Sort {
+            field: "created_at",
+            descending: false,
+        });
+        users.sort_by(|a, b| {
+            let order = match sort.field {
+                "username" => a.username.cmp(&b.username),
+                "email" => a.email.cmp(&b.email),
+                _ => a.created_at.cmp(&b.created_at),
+            };
+            if sort.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        let total = users.len() as u64;
+        let page = users
+            .into_iter()
+            .skip(query.offset as usize)
+            .take(query.limit as usize)
+            .collect();
+        Ok((page, total))
+    }
+
+    async fn create_refresh_token(
+        &self,
+        user_id: &str,
+        family_id: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> StoreResult<()> {
+        let token = RefreshTokenDocument {
+            id: ObjectId::new(),
+            user_id: user_id.to_string(),
+            family_id: family_id.to_string(),
+            token_hash: token_hash.to_string(),
+            expires_at: BsonDateTime::from_chrono(expires_at),
+            revoked_at: None,
+            created_at: BsonDateTime::now(),
+        };
+        self.refresh_tokens.lock().unwrap().push(token);
+        Ok(())
+    }
+
+    async fn get_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> StoreResult<Option<RefreshTokenDocument>> {
+        let tokens = self.refresh_tokens.lock().unwrap();
+        Ok(tokens
+            .iter()
+            .find(|token| token.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn consume_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> StoreResult<Option<RefreshTokenDocument>> {
+        let mut tokens = self.refresh_tokens.lock().unwrap();
+        // Like the MongoDB query, this returns the token as it was before revocation.
+        Ok(tokens
+            .iter_mut()
+            .find(|token| token.token_hash == token_hash && token.revoked_at.is_none())
+            .map(|token| {
+                let current = token.clone();
+                token.revoked_at = Some(BsonDateTime::now());
+                current
+            }))
+    }
+
+    async fn revoke_session(&self, family_id: &str) -> StoreResult<()> {
+        self.revoke_where(|token| token.family_id == family_id);
+        Ok(())
+    }
+
+    async fn revoke_user_sessions(&self, user_id: &str) -> StoreResult<()> {
+        self.revoke_where(|token| token.user_id == user_id);
+        Ok(())
+    }
+
+    async fn is_session_active(&self, family_id: &str) -> StoreResult<bool> {
+        let now = BsonDateTime::now();
+        let tokens = self.refresh_tokens.lock().unwrap();
+        Ok(tokens.iter().any(|token| {
+            token.family_id == family_id && token.revoked_at.is_none() && token.expires_at > now
+        }))
+    }
+
+    async fn ping(&self) -> StoreResult<()> {
+        Ok(())
     }
 }
 "#;
@@ -7,13 +7,21 @@ pub fn load_template() -> Vec<(PathBuf, &'static str)> {
         ("Cargo.toml".into(), files::CARGO_TOML),
         ("src/main.rs".into(), files::MAIN_RS),
         ("src/routes/mod.rs".into(), files::ROUTES_MOD),
+        ("src/config/mod.rs".into(), common::files::CONFIG),
         ("src/fairings/mod.rs".into(), common::files::CORS),
         ("src/guards/mod.rs".into(), common::files::AUTH_GUARD),
-        ("src/catchers/mod.rs".into(), files::CATACHERS),
-        ("src/options/mod.rs".into(), files::OPTIONS),
+        ("src/health/mod.rs".into(), common::files::HEALTH),
+        ("src/catchers/mod.rs".into(), common::files::CATCHERS),
         ("src/repositories/mod.rs".into(), files::REPOSITORIES),
+        ("src/repositories/memory.rs".into(), files::MEMORY_STORE),
         ("src/db/mod.rs".into(), files::DB),
+        ("build.rs".into(), common::files::BUILD_RS),
+        ("src/errors/mod.rs".into(), common::files::ERRORS),
         ("src/models/mod.rs".into(), files::MODELS),
+        ("src/models/user_schema.json".into(), files::USER_SCHEMA),
+        ("src/openapi/mod.rs".into(), common::files::OPENAPI),
+        ("src/pagination/mod.rs".into(), common::files::PAGINATION),
+        ("src/validation/mod.rs".into(), common::files::VALIDATION),
         ("src/auth/mod.rs".into(), common::files::BASIC_AUTH),
         ("src/middleware/mod.rs".into(), common::files::MIDDLEWARE),
         ("Rocket.toml".into(), common::files::ROCKET_CONFIG),
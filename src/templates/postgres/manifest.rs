@@ -7,13 +7,24 @@ pub fn load_template() -> Vec<(PathBuf, &'static str)> {
         ("Cargo.toml".into(), files::CARGO_TOML),
         ("src/main.rs".into(), files::MAIN_RS),
         ("src/routes/mod.rs".into(), files::ROUTES_MOD),
+        ("src/config/mod.rs".into(), common::files::CONFIG),
         ("src/fairings/mod.rs".into(), common::files::CORS),
         ("src/guards/mod.rs".into(), common::files::AUTH_GUARD),
-        ("src/catchers/mod.rs".into(), files::CATCHERS),
-        ("src/options/mod.rs".into(), files::OPTIONS),
+        ("src/health/mod.rs".into(), common::files::HEALTH),
+        ("src/catchers/mod.rs".into(), common::files::CATCHERS),
         ("src/repositories/mod.rs".into(), files::REPOSITORIES),
+        ("src/repositories/memory.rs".into(), files::MEMORY_STORE),
         ("src/db/mod.rs".into(), files::DB),
+        ("src/errors/mod.rs".into(), common::files::ERRORS),
+        (
+            "src/db/migrations.rs".into(),
+            common::files::EMBEDDED_MIGRATIONS,
+        ),
+        ("build.rs".into(), common::files::BUILD_RS),
         ("src/models/mod.rs".into(), files::MODELS),
+        ("src/openapi/mod.rs".into(), common::files::OPENAPI),
+        ("src/pagination/mod.rs".into(), common::files::PAGINATION),
+        ("src/validation/mod.rs".into(), common::files::VALIDATION),
         ("src/auth/mod.rs".into(), common::files::BASIC_AUTH),
         ("src/middleware/mod.rs".into(), common::files::MIDDLEWARE),
         ("Rocket.toml".into(), common::files::ROCKET_CONFIG),
@@ -23,5 +34,25 @@ pub fn load_template() -> Vec<(PathBuf, &'static str)> {
             "migrations/001_create_users_table.sql".into(),
             files::MIGRATIONS,
         ),
+        (
+            "migrations/001_create_users_table.down.sql".into(),
+            files::MIGRATIONS_DOWN,
+        ),
+        (
+            "migrations/002_create_refresh_tokens_table.sql".into(),
+            files::REFRESH_TOKENS_MIGRATION,
+        ),
+        (
+            "migrations/002_create_refresh_tokens_table.down.sql".into(),
+            files::REFRESH_TOKENS_MIGRATION_DOWN,
+        ),
+        (
+            "migrations/003_add_roles_to_users.sql".into(),
+            files::ROLES_MIGRATION,
+        ),
+        (
+            "migrations/003_add_roles_to_users.down.sql".into(),
+            files::ROLES_MIGRATION_DOWN,
+        ),
     ]
 }
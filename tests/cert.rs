@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use rocket_cli::cert::DEFAULT_HOSTS;
+    use rocket_cli::commands::cert::execute;
+    use rocket_cli::error::Error;
+    use rocket_cli::scaffold::Scaffolder;
+    use std::fs;
+    use std::path::Path;
+
+    fn hosts() -> Vec<String> {
+        DEFAULT_HOSTS.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[cfg(feature = "cert")]
+    #[test]
+    fn test_cert_enables_debug_tls() {
+        let dir = tempfile::tempdir().unwrap();
+        Scaffolder::new("minimal")
+            .var("project_name", "my-api")
+            .render()
+            .unwrap()
+            .write_to(dir.path())
+            .unwrap();
+
+        let report = execute(dir.path(), Path::new("certs"), &hosts(), false).unwrap();
+        assert_eq!(report.cert, Path::new("certs/cert.pem"));
+
+        for file in ["ca.pem", "ca-key.pem", "cert.pem", "key.pem"] {
+            assert!(dir.path().join("certs").join(file).exists(), "{}", file);
+        }
+
+        let rocket_toml = fs::read_to_string(dir.path().join("Rocket.toml")).unwrap();
+        assert!(
+            rocket_toml
+                .contains("[debug.tls]\ncerts = \"certs/cert.pem\"\nkey = \"certs/key.pem\"")
+        );
+        assert!(!rocket_toml.contains("# [debug.tls]"));
+
+        let cargo_toml = fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains(r#"features = ["json", "tls"]"#));
+
+        let again = execute(dir.path(), Path::new("certs"), &hosts(), false);
+        assert!(matches!(again, Err(Error::Validation(_))));
+        assert!(execute(dir.path(), Path::new("certs"), &hosts(), true).is_ok());
+    }
+
+    #[cfg(feature = "cert")]
+    #[test]
+    fn test_cert_keeps_unrelated_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        Scaffolder::new("minimal")
+            .var("project_name", "my-api")
+            .render()
+            .unwrap()
+            .write_to(dir.path())
+            .unwrap();
+
+        let path = dir.path().join("Rocket.toml");
+        let rocket_toml = fs::read_to_string(&path)
+            .unwrap()
+            .replace(
+                "# Resource limits\n",
+                "# Resource limits\n# key = \"vault/key.pem\"\n",
+            )
+            .replace(
+                "[release]\n",
+                "# TLS for local HTTPS is set above\n[release]\n",
+            );
+        fs::write(&path, rocket_toml).unwrap();
+
+        execute(dir.path(), Path::new("certs"), &hosts(), false).unwrap();
+
+        let rocket_toml = fs::read_to_string(&path).unwrap();
+        assert!(!rocket_toml.contains("# [debug.tls]"));
+        assert!(rocket_toml.contains("# Resource limits\n# key = \"vault/key.pem\"\n"));
+        assert!(rocket_toml.contains("# TLS for local HTTPS is set above\n[release]"));
+    }
+
+    #[cfg(not(feature = "cert"))]
+    #[test]
+    fn test_missing_feature_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        Scaffolder::new("minimal")
+            .var("project_name", "my-api")
+            .render()
+            .unwrap()
+            .write_to(dir.path())
+            .unwrap();
+
+        match execute(dir.path(), Path::new("certs"), &hosts(), false) {
+            Err(Error::Validation(message)) => assert!(message.contains("--features cert")),
+            other => panic!(
+                "Expected a validation error, got {:?}",
+                other.map(|r| r.cert)
+            ),
+        }
+        assert!(!dir.path().join("certs").exists());
+        let rocket_toml = fs::read_to_string(dir.path().join("Rocket.toml")).unwrap();
+        assert!(rocket_toml.contains("# [debug.tls]"));
+    }
+
+    #[test]
+    fn test_cert_requires_a_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = execute(dir.path(), Path::new("certs"), &hosts(), false);
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod tests {
+    use log::LevelFilter;
+    use rocket_cli::logger::level_for;
+    use rocket_cli::output::ColorChoice;
+
+    #[test]
+    fn test_level_for_verbosity_flags() {
+        assert_eq!(level_for(0, false), LevelFilter::Info);
+        assert_eq!(level_for(1, false), LevelFilter::Debug);
+        assert_eq!(level_for(2, false), LevelFilter::Trace);
+        assert_eq!(level_for(0, true), LevelFilter::Error);
+    }
+
+    #[test]
+    fn test_explicit_color_choice_ignores_environment() {
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+}
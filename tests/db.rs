@@ -0,0 +1,296 @@
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use rocket_cli::commands::SeedArgs;
+    use rocket_cli::commands::db::{indexes, migrate, new_migration, rollback, seed, status};
+    use rocket_cli::db::migrations::MigrationState;
+    use rocket_cli::db::seeds::{self, Seed};
+    use rocket_cli::error::Error;
+    use std::fs;
+    use std::path::Path;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let migrations = dir.path().join("migrations");
+        fs::create_dir(&migrations).unwrap();
+        fs::write(dir.path().join(".env"), "DATABASE_URL=sqlite:app.db\n").unwrap();
+
+        fs::write(
+            migrations.join("001_create_users.sql"),
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL);",
+        )
+        .unwrap();
+        fs::write(
+            migrations.join("001_create_users.down.sql"),
+            "DROP TABLE users;",
+        )
+        .unwrap();
+        fs::write(
+            migrations.join("002_add_roles.sql"),
+            "ALTER TABLE users ADD COLUMN roles TEXT NOT NULL DEFAULT 'user';",
+        )
+        .unwrap();
+        fs::write(
+            migrations.join("002_add_roles.down.sql"),
+            "ALTER TABLE users DROP COLUMN roles;",
+        )
+        .unwrap();
+        dir
+    }
+
+    fn states(project: &Path) -> Vec<MigrationState> {
+        status(project, None)
+            .unwrap()
+            .migrations
+            .iter()
+            .map(|m| m.state)
+            .collect()
+    }
+
+    #[test]
+    fn test_migrate_and_rollback() {
+        let dir = project();
+        assert_eq!(
+            states(dir.path()),
+            [MigrationState::Pending, MigrationState::Pending]
+        );
+
+        let report = migrate(dir.path(), None).unwrap();
+        assert_eq!(report.applied.len(), 2);
+        assert!(dir.path().join("app.db").exists());
+        assert_eq!(
+            states(dir.path()),
+            [MigrationState::Applied, MigrationState::Applied]
+        );
+
+        assert!(migrate(dir.path(), None).unwrap().applied.is_empty());
+
+        let report = rollback(dir.path(), None, 1).unwrap();
+        assert_eq!(report.reverted.len(), 1);
+        assert_eq!(report.reverted[0].name, "add_roles");
+        assert_eq!(
+            states(dir.path()),
+            [MigrationState::Applied, MigrationState::Pending]
+        );
+
+        let report = migrate(dir.path(), None).unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.applied[0].name, "add_roles");
+    }
+
+    #[test]
+    fn test_failed_migration_is_rolled_back() {
+        let dir = project();
+        fs::write(
+            dir.path().join("migrations/003_broken.sql"),
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY); INSERT INTO missing VALUES (1);",
+        )
+        .unwrap();
+
+        let err = migrate(dir.path(), None).unwrap_err();
+        assert!(matches!(err, Error::Database(_)), "{}", err);
+        assert!(err.to_string().contains("3_broken"), "{}", err);
+
+        // The migrations before it stay applied; the broken one left nothing behind.
+        assert_eq!(
+            states(dir.path()),
+            [
+                MigrationState::Applied,
+                MigrationState::Applied,
+                MigrationState::Pending
+            ]
+        );
+        fs::write(
+            dir.path().join("migrations/003_broken.sql"),
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY);",
+        )
+        .unwrap();
+        assert_eq!(migrate(dir.path(), None).unwrap().applied.len(), 1);
+    }
+
+    #[test]
+    fn test_rollback_requires_down_migration() {
+        let dir = project();
+        fs::remove_file(dir.path().join("migrations/002_add_roles.down.sql")).unwrap();
+        migrate(dir.path(), None).unwrap();
+
+        let err = rollback(dir.path(), None, 2).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{}", err);
+        assert_eq!(
+            states(dir.path()),
+            [MigrationState::Applied, MigrationState::Applied]
+        );
+    }
+
+    #[test]
+    fn test_new_migration_sorts_last() {
+        let dir = project();
+        let report = new_migration(dir.path(), "Add avatar to users").unwrap();
+
+        let migration = report.migration;
+        assert_eq!(migration.name, "add_avatar_to_users");
+        assert!(migration.version > 2);
+        assert!(migration.up.exists());
+        assert!(migration.down.unwrap().exists());
+        assert_eq!(states(dir.path()).len(), 3);
+    }
+
+    #[test]
+    fn test_unsupported_database_url() {
+        let dir = project();
+        let err = migrate(dir.path(), Some("mongodb://localhost:27017")).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_indexes_require_mongodb() {
+        let dir = project();
+        let err = indexes(dir.path(), None, false).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_seed_runs_files_and_creates_admin_once() {
+        let dir = project();
+        migrate(dir.path(), None).unwrap();
+        fs::write(
+            dir.path().join("Rocket.toml"),
+            "[default.auth.argon2]\nmemory_kib = 8192\niterations = 3\nparallelism = 1\n",
+        )
+        .unwrap();
+
+        let seeds = dir.path().join("seeds");
+        fs::create_dir(&seeds).unwrap();
+        fs::write(
+            seeds.join("01_users.sql"),
+            "INSERT INTO users (email) VALUES ('sql@example.com');",
+        )
+        .unwrap();
+        fs::write(
+            seeds.join("02_users.json"),
+            r#"{"users": [{"email": "o'brien@example.com", "roles": "user"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            seeds.join("03_users.toml"),
+            "[[users]]\nemail = \"toml@example.com\"\n",
+        )
+        .unwrap();
+
+        let args = SeedArgs {
+            admin: true,
+            admin_username: "root".into(),
+            admin_email: "root@example.com".into(),
+            admin_password: Some("correct horse".into()),
+        };
+        // The users table in this fixture has no username or password columns.
+        let connection = rusqlite::Connection::open(dir.path().join("app.db")).unwrap();
+        connection
+            .execute_batch("ALTER TABLE users ADD COLUMN username TEXT; ALTER TABLE users ADD COLUMN password TEXT;")
+            .unwrap();
+
+        let report = seed(dir.path(), None, &args).unwrap();
+        let rows: Vec<_> = report.seeds.iter().map(|s| s.rows).collect();
+        assert_eq!(rows, [None, Some(1), Some(1)]);
+        assert!(report.admin.unwrap().created);
+
+        let (hash, roles): (String, String) = connection
+            .query_row(
+                "SELECT password, roles FROM users WHERE email = 'root@example.com'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(
+            hash.starts_with("$argon2id$v=19$m=8192,t=3,p=1$"),
+            "{}",
+            hash
+        );
+        assert_eq!(roles, "user,admin");
+
+        let report = seed(
+            dir.path(),
+            None,
+            &SeedArgs {
+                admin_password: Some("another".into()),
+                ..args
+            },
+        )
+        .unwrap();
+        assert!(!report.admin.unwrap().created);
+
+        let users: i64 = connection
+            .query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(users, 7);
+    }
+
+    #[test]
+    fn test_json_seed_tables_keep_file_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seed.json");
+        fs::write(
+            &path,
+            r#"{"users": [{"id": 1}], "posts": [{"user_id": 1}]}"#,
+        )
+        .unwrap();
+
+        let Seed::Records(records) = seeds::load(&path).unwrap() else {
+            panic!("Expected records");
+        };
+        let tables: Vec<_> = records.iter().map(|(table, _)| table.as_str()).collect();
+        assert_eq!(tables, ["users", "posts"]);
+    }
+
+    #[test]
+    fn test_invalid_seed_file_is_rejected() {
+        let dir = project();
+        migrate(dir.path(), None).unwrap();
+        fs::create_dir(dir.path().join("seeds")).unwrap();
+        fs::write(
+            dir.path().join("seeds/users.json"),
+            r#"{"users": {"email": "x"}}"#,
+        )
+        .unwrap();
+
+        let args = SeedArgs {
+            admin: false,
+            admin_username: "admin".into(),
+            admin_email: "admin@example.com".into(),
+            admin_password: None,
+        };
+        let err = seed(dir.path(), None, &args).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{}", err);
+    }
+}
+
+#[cfg(all(test, not(feature = "postgres")))]
+mod without_postgres {
+    use rocket_cli::commands::db::migrate;
+    use rocket_cli::error::Error;
+
+    #[test]
+    fn test_missing_driver_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = migrate(dir.path(), Some("postgres://localhost/app")).unwrap_err();
+        match err {
+            Error::Validation(message) => assert!(message.contains("--features postgres")),
+            other => panic!("Expected a validation error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "mongodb")))]
+mod without_mongodb {
+    use rocket_cli::commands::db::indexes;
+    use rocket_cli::error::Error;
+
+    #[test]
+    fn test_missing_driver_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = indexes(dir.path(), Some("mongodb://localhost/app"), false).unwrap_err();
+        match err {
+            Error::Validation(message) => assert!(message.contains("--features mongodb")),
+            other => panic!("Expected a validation error, got {:?}", other),
+        }
+    }
+}
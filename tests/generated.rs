@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    // These fetch and build the generated projects' dependencies, so they are
+    // ignored by default. CI runs them with `cargo test --test generated -- --ignored`.
+    use rocket_cli::scaffold::Scaffolder;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Renders `template` with `features` and runs `cargo check --all-targets` on it.
+    fn check(template: &str, features: &[&str]) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut scaffolder = Scaffolder::new(template).var("project_name", "generated-check");
+        for &feature in features {
+            scaffolder = scaffolder.feature(feature);
+        }
+        scaffolder.render().unwrap().write_to(dir.path()).unwrap();
+
+        // Shared by every check, so the dependencies are only built once.
+        let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("generated");
+        let status = Command::new(env!("CARGO"))
+            .args(["check", "--all-targets", "--quiet"])
+            .current_dir(dir.path())
+            .env("CARGO_TARGET_DIR", target_dir)
+            .status()
+            .unwrap();
+
+        assert!(
+            status.success(),
+            "the {} template with {:?} does not compile",
+            template,
+            features
+        );
+    }
+
+    #[test]
+    #[ignore = "fetches and builds the generated project's dependencies"]
+    fn test_minimal_project_compiles() {
+        check("minimal", &[]);
+    }
+
+    #[test]
+    #[ignore = "fetches and builds the generated project's dependencies"]
+    fn test_mongodb_project_compiles() {
+        check("mongodb", &[]);
+        check("mongodb", &["openapi"]);
+    }
+
+    #[test]
+    #[ignore = "fetches and builds the generated project's dependencies"]
+    fn test_postgres_project_compiles() {
+        check("postgres", &[]);
+        check("postgres", &["migrations", "openapi"]);
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use rocket_cli::error::Error;
+    use rocket_cli::git::{GitOptions, GitOutcome, init_repository};
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn test_validate_rejects_malformed_author() {
+        let options = GitOptions {
+            author: Some("Jane Doe".into()),
+            ..GitOptions::default()
+        };
+
+        assert!(matches!(options.validate(), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_init_repository_commits_generated_files() {
+        if Command::new("git").arg("--version").output().is_err() {
+            eprintln!("git is not installed; skipping");
+            return;
+        }
+
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("project");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+
+        let options = GitOptions {
+            branch: Some("trunk".into()),
+            author: Some("Jane Doe <jane@example.com>".into()),
+            ..GitOptions::default()
+        };
+
+        match init_repository(&dir, &options).expect("git step should not fail") {
+            GitOutcome::Committed { branch, commit } => {
+                assert_eq!(branch, "trunk");
+                assert!(!commit.is_empty());
+            }
+            other => panic!("Expected a commit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_init_repository_skips_commit_without_a_user_name() {
+        if Command::new("git").arg("--version").output().is_err() {
+            eprintln!("git is not installed; skipping");
+            return;
+        }
+
+        let root = tempfile::tempdir().unwrap();
+        let config = root.path().join("gitconfig");
+        fs::write(&config, "[user]\n\temail = jane@example.com\n").unwrap();
+        // SAFETY: the other tests in this file pass an explicit author, so the
+        // isolated config doesn't change what they do.
+        unsafe {
+            std::env::set_var("GIT_CONFIG_GLOBAL", &config);
+            std::env::set_var("GIT_CONFIG_NOSYSTEM", "1");
+        }
+
+        let dir = root.path().join("project");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+
+        match init_repository(&dir, &GitOptions::default()).expect("git step should not fail") {
+            GitOutcome::Initialized { reason } => assert!(reason.contains("user.name")),
+            other => panic!("Expected an initialized repository, got {:?}", other),
+        }
+        assert!(dir.join(".git").exists());
+    }
+}
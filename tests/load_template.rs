@@ -15,6 +15,8 @@ mod tests {
             PathBuf::from("src/main.rs"),
             PathBuf::from("src/routes/mod.rs"),
             PathBuf::from("src/fairings/mod.rs"),
+            PathBuf::from("src/health/mod.rs"),
+            PathBuf::from("build.rs"),
             PathBuf::from("Rocket.toml"),
             PathBuf::from(".gitignore"),
             PathBuf::from(".env"),
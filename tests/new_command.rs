@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use rocket_cli::commands::new::{NewOptions, execute};
+    use rocket_cli::error::Error;
+
+    #[test]
+    fn test_execute_rejects_unknown_template() {
+        let result = execute(
+            "rocket-cli-unknown-template-test".into(),
+            NewOptions {
+                template: "does-not-exist".into(),
+                ..NewOptions::default()
+            },
+        );
+
+        match result {
+            Err(Error::TemplateNotFound(name)) => assert_eq!(name, "does-not-exist"),
+            other => panic!("Expected TemplateNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_execute_rejects_existing_directory() {
+        let result = execute(".".into(), NewOptions::default());
+        let error = result.expect_err("Expected an existing directory to be rejected");
+
+        assert!(matches!(error, Error::Validation(_)));
+        assert_eq!(error.exit_code(), 2);
+        assert_eq!(error.code(), "validation_error");
+    }
+
+    #[test]
+    fn test_execute_rejects_invalid_cors_origin() {
+        let result = execute(
+            "rocket-cli-invalid-origin-test".into(),
+            NewOptions {
+                cors_origins: vec!["https://app.example.com/path".into()],
+                ..NewOptions::default()
+            },
+        );
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_execute_rejects_embedded_migrations_without_sql() {
+        let result = execute(
+            "rocket-cli-embed-migrations-test".into(),
+            NewOptions {
+                template: "mongodb".into(),
+                embed_migrations: true,
+                ..NewOptions::default()
+            },
+        );
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(!std::path::Path::new("rocket-cli-embed-migrations-test").exists());
+    }
+
+    #[test]
+    fn test_execute_rejects_openapi_without_models() {
+        let result = execute(
+            "rocket-cli-openapi-test".into(),
+            NewOptions {
+                template: "minimal".into(),
+                openapi: true,
+                ..NewOptions::default()
+            },
+        );
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(!std::path::Path::new("rocket-cli-openapi-test").exists());
+    }
+}
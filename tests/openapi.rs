@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use rocket_cli::commands::openapi::export;
+    use rocket_cli::error::Error;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_export_requires_an_openapi_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = export(dir.path(), Path::new("openapi.json"));
+        assert!(matches!(result, Err(Error::Validation(_))));
+
+        // A project generated without --openapi has no spec to export.
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        let result = export(dir.path(), Path::new("openapi.json"));
+        match result {
+            Err(Error::Validation(message)) => assert!(message.contains("--openapi")),
+            other => panic!("Expected a validation error, got {:?}", other),
+        }
+        assert!(!dir.path().join("openapi.json").exists());
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use rocket_cli::error::Error;
+    use rocket_cli::scaffold::Scaffolder;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_render_substitutes_variables() {
+        let project = Scaffolder::new("minimal")
+            .var("project_name", "my-api")
+            .render()
+            .expect("minimal template should render");
+
+        assert_eq!(project.template(), "minimal");
+        assert_eq!(project.vars()["project_name"], "my-api");
+
+        let cargo_toml = project.file("Cargo.toml").expect("Cargo.toml is rendered");
+        assert!(cargo_toml.contains(r#"name = "my-api""#));
+        assert!(!cargo_toml.contains("{{project_name}}"));
+    }
+
+    #[test]
+    fn test_render_unknown_template() {
+        let result = Scaffolder::new("does-not-exist").render();
+
+        assert!(matches!(result, Err(Error::TemplateNotFound(_))));
+    }
+
+    #[test]
+    fn test_render_rejects_reserved_variables() {
+        for name in ["template", "features", "cli"] {
+            let result = Scaffolder::new("minimal")
+                .var("project_name", "my-api")
+                .var(name, "custom")
+                .render();
+
+            assert!(matches!(result, Err(Error::Validation(_))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_gitignore_covers_every_template() {
+        let render = |template| {
+            Scaffolder::new(template)
+                .var("project_name", "ignored")
+                .render()
+                .unwrap()
+                .file(".gitignore")
+                .unwrap()
+                .to_string()
+        };
+
+        let minimal = render("minimal");
+        let postgres = render("postgres");
+
+        for entry in ["/target", ".env.*", "dist/", "/certs/"] {
+            assert!(minimal.contains(entry));
+        }
+        assert_eq!(minimal, postgres);
+    }
+
+    #[test]
+    fn test_write_to_creates_every_file() {
+        let root = std::env::temp_dir().join(format!("rocket-cli-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let project = Scaffolder::new("postgres")
+            .var("project_name", "pg-api")
+            .render()
+            .expect("postgres template should render");
+        project.write_to(&root).expect("project should be written");
+
+        for file in project.files() {
+            let written = fs::read_to_string(root.join(&file.path)).expect("file exists on disk");
+            assert_eq!(written, file.contents);
+        }
+        assert!(Path::new(&root).join("migrations").is_dir());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,439 @@
+#[cfg(test)]
+mod tests {
+    use rocket_cli::scaffold::{RenderedProject, Scaffolder};
+
+    fn render(template: &str) -> RenderedProject {
+        Scaffolder::new(template)
+            .var("project_name", "my-api")
+            .render()
+            .expect("template should render")
+    }
+
+    #[test]
+    fn test_auth_config_is_filled_from_project_name() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let rocket_toml = project.file("Rocket.toml").unwrap();
+
+            assert!(rocket_toml.contains("[default.auth]"));
+            assert!(rocket_toml.contains(r#"issuer = "my-api""#));
+            assert!(rocket_toml.contains(r#"audience = "my-api-api""#));
+            assert!(rocket_toml.contains(r#"secret_env = "AUTH_KEY""#));
+        }
+
+        assert!(
+            !render("minimal")
+                .file("Rocket.toml")
+                .unwrap()
+                .contains("[default.auth]")
+        );
+    }
+
+    #[test]
+    fn test_env_defines_the_auth_secret_read_by_the_code() {
+        for template in ["mongodb", "postgres"] {
+            let env = render(template).file(".env").unwrap().to_string();
+
+            assert!(env.lines().any(|line| line.starts_with("AUTH_KEY=")));
+            assert!(!env.contains("JWT_SECRET"));
+        }
+    }
+
+    #[test]
+    fn test_refresh_tokens_are_persisted_and_routed() {
+        let postgres = render("postgres");
+        let migration = postgres
+            .file("migrations/002_create_refresh_tokens_table.sql")
+            .expect("refresh token migration is generated");
+        assert!(migration.contains("CREATE TABLE refresh_tokens"));
+
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let routes = project.file("src/routes/mod.rs").unwrap();
+            let repositories = project.file("src/repositories/mod.rs").unwrap();
+
+            assert!(routes.contains(r#"#[post("/refresh""#));
+            assert!(routes.contains("revoke_user_sessions"));
+            assert!(repositories.contains("async fn consume_refresh_token"));
+            assert!(repositories.contains("async fn is_session_active"));
+        }
+    }
+
+    #[test]
+    fn test_auth_guard_accepts_bearer_tokens() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let guard = project.file("src/guards/mod.rs").unwrap();
+            let routes = project.file("src/routes/mod.rs").unwrap();
+
+            assert!(guard.contains(r#"get_one("Authorization")"#));
+            assert!(guard.contains("pub subject: String"));
+            assert!(guard.contains("pub roles: Vec<String>"));
+            assert!(
+                routes.contains(r#"#[post("/login?<include_token>", data = "<credentials>")]"#)
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_updates_require_ownership_or_admin() {
+        let postgres = render("postgres");
+        let migration = postgres
+            .file("migrations/003_add_roles_to_users.sql")
+            .expect("roles migration is generated");
+        assert!(migration.contains("ADD COLUMN roles"));
+
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let guard = project.file("src/guards/mod.rs").unwrap();
+            let routes = project.file("src/routes/mod.rs").unwrap();
+
+            assert!(guard.contains("pub struct RequireRole<R: Role>"));
+            assert!(routes.contains("auth.is_self_or_admin(&user.email)"));
+            assert_eq!(routes.matches("authorize_owner(&auth, repo").count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_routes_depend_on_the_user_store_trait() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let repositories = project.file("src/repositories/mod.rs").unwrap();
+            let routes = project.file("src/routes/mod.rs").unwrap();
+            let guard = project.file("src/guards/mod.rs").unwrap();
+            let db = project.file("src/db/mod.rs").unwrap();
+
+            assert!(repositories.contains("pub trait UserStore: Send + Sync"));
+            assert!(repositories.contains("impl UserStore for UserRepository"));
+            assert!(repositories.contains("#[cfg(test)]\npub mod memory;"));
+            assert!(
+                project
+                    .file("src/repositories/memory.rs")
+                    .unwrap()
+                    .contains("impl UserStore for InMemoryUserStore")
+            );
+            assert!(!routes.contains("UserRepository"));
+            assert_eq!(routes.matches("&State<Arc<dyn UserStore>>").count(), 9);
+            assert!(guard.contains("state::<Arc<dyn UserStore>>()"));
+            assert!(db.contains("let store: Arc<dyn UserStore>"));
+        }
+    }
+
+    #[test]
+    fn test_users_are_listed_a_page_at_a_time() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let routes = project.file("src/routes/mod.rs").unwrap();
+            let repositories = project.file("src/repositories/mod.rs").unwrap();
+            let main = project.file("src/main.rs").unwrap();
+
+            assert!(main.contains("mod pagination;"));
+            assert!(
+                project
+                    .file("src/pagination/mod.rs")
+                    .unwrap()
+                    .contains("pub struct Pagination")
+            );
+            assert!(routes.contains(r#"#[get("/users?<pagination..>")]"#));
+            assert!(routes.contains("_admin: RequireRole<Admin>"));
+            assert!(routes.contains("Page::new(users, total, &pagination, \"/users\")"));
+            assert!(repositories.contains("async fn list_users(&self, query: &UserQuery<'_>)"));
+        }
+
+        let postgres = render("postgres");
+        let repositories = postgres.file("src/repositories/mod.rs").unwrap();
+        assert!(repositories.contains("LIMIT ? OFFSET ?"));
+
+        let mongodb = render("mongodb");
+        let repositories = mongodb.file("src/repositories/mod.rs").unwrap();
+        assert!(repositories.contains(".skip(query.offset)"));
+        assert!(repositories.contains(".limit(query.limit as i64)"));
+    }
+
+    #[test]
+    fn test_credentials_are_validated() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let routes = project.file("src/routes/mod.rs").unwrap();
+            let models = project.file("src/models/mod.rs").unwrap();
+            let main = project.file("src/main.rs").unwrap();
+
+            assert!(project.file("src/validation/mod.rs").is_some());
+            assert!(main.contains("mod validation;"));
+            assert!(models.contains("impl Validate for RegistrationCredentials"));
+            assert!(models.contains("impl Validate for LoginCredentials"));
+            assert_eq!(routes.matches("credentials: Validated<Json<").count(), 3);
+            assert!(!routes.contains("credentials: Json<"));
+        }
+    }
+
+    #[test]
+    fn test_errors_are_problem_documents() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let main = project.file("src/main.rs").unwrap();
+            let routes = project.file("src/routes/mod.rs").unwrap();
+            let errors = project.file("src/errors/mod.rs").unwrap();
+            let catchers = project.file("src/catchers/mod.rs").unwrap();
+
+            assert!(main.contains(".register(\"/\", catchers::all())"));
+            assert!(main.contains(".attach(fairings::RequestId)"));
+            assert!(errors.contains("ContentType::new(\"application\", \"problem+json\")"));
+            assert!(catchers.contains("#[catch(default)]"));
+            assert!(routes.contains("Err(ApiError::new(Status::NotFound, \"User not found\"))"));
+            assert!(!routes.contains("ErrorResponse"));
+        }
+
+        let minimal = render("minimal");
+        assert!(minimal.file("src/errors/mod.rs").is_none());
+    }
+
+    #[test]
+    fn test_passwords_use_argon2_with_configurable_cost() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let auth = project.file("src/auth/mod.rs").unwrap();
+            let routes = project.file("src/routes/mod.rs").unwrap();
+            let rocket_toml = project.file("Rocket.toml").unwrap();
+
+            assert!(auth.contains("argon2::Algorithm::Argon2id"));
+            assert!(auth.contains("bcrypt::verify"));
+            assert!(routes.contains("needs_rehash(&"));
+            assert!(rocket_toml.contains("[default.auth.argon2]"));
+        }
+    }
+
+    #[test]
+    fn test_cors_origins_are_configurable() {
+        let project = Scaffolder::new("minimal")
+            .var("project_name", "my-api")
+            .var(
+                "cors_origins",
+                vec!["https://app.example.com", "http://a\\b"],
+            )
+            .var("cors_credentials", false)
+            .render()
+            .unwrap();
+        let rocket_toml = project.file("Rocket.toml").unwrap();
+
+        assert!(
+            rocket_toml.contains(r#"allowed_origins = ["https://app.example.com", 'http://a\b']"#)
+        );
+        assert!(rocket_toml.contains("allow_credentials = false"));
+        assert!(
+            render("minimal")
+                .file("Rocket.toml")
+                .unwrap()
+                .contains("allow_credentials = true")
+        );
+
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let fairings = project.file("src/fairings/mod.rs").unwrap();
+
+            assert!(fairings.contains("Status::NoContent"));
+            assert!(!fairings.contains(r#""Access-Control-Allow-Origin", "*""#));
+            assert!(project.file("src/options/mod.rs").is_none());
+        }
+    }
+
+    #[test]
+    fn test_settings_come_from_the_app_config() {
+        for template in ["minimal", "mongodb", "postgres"] {
+            let project = render(template);
+            let main = project.file("src/main.rs").unwrap();
+            let config = project.file("src/config/mod.rs").expect("config module");
+
+            assert!(main.contains("rocket::custom(config::figment())"));
+            assert!(main.contains(".attach(config::AppConfig::fairing())"));
+            assert!(config.contains("pub struct AppConfig"));
+
+            // Build scripts run before any configuration exists.
+            for file in project
+                .files()
+                .iter()
+                .filter(|f| f.path.as_os_str() != "build.rs")
+            {
+                assert!(
+                    !file.contents.contains("env::var("),
+                    "{} reads the environment directly",
+                    file.path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rocket_toml_has_profiles_and_optional_tls() {
+        let project = render("minimal");
+        let rocket_toml = project.file("Rocket.toml").unwrap();
+
+        assert!(rocket_toml.contains("\n[debug]\n"));
+        assert!(rocket_toml.contains("\n[release]\n"));
+        assert!(rocket_toml.contains("# secret_key = "));
+        assert!(rocket_toml.contains("# [debug.tls]"));
+        assert!(rocket_toml.contains("# workers = "));
+        assert!(!rocket_toml.contains("[global]"));
+        assert!(!rocket_toml.contains("\n[default.tls]"));
+
+        let project = Scaffolder::new("postgres")
+            .var("project_name", "my-api")
+            .var("workers", "4")
+            .feature("tls")
+            .render()
+            .unwrap();
+        let rocket_toml = project.file("Rocket.toml").unwrap();
+        let cargo_toml = project.file("Cargo.toml").unwrap();
+
+        assert!(rocket_toml.contains("\n[debug.tls]\n"));
+        assert!(rocket_toml.contains("\nworkers = 4\n"));
+        assert!(cargo_toml.contains(r#"features = ["json", "tls"]"#));
+    }
+
+    #[test]
+    fn test_db_fairing_retries_instead_of_panicking() {
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let db = project.file("src/db/mod.rs").unwrap();
+            let rocket_toml = project.file("Rocket.toml").unwrap();
+
+            assert!(db.contains("AdHoc::try_on_ignite"));
+            assert!(db.contains("config.max_connections"));
+            assert!(!db.contains("panic!"));
+            assert!(rocket_toml.contains("retry_for_secs = 30"));
+        }
+    }
+
+    #[test]
+    fn test_migrations_can_be_embedded() {
+        let project = render("postgres");
+        assert!(project.file("src/db/migrations.rs").is_none());
+        assert!(!project.file("build.rs").unwrap().contains("migrations"));
+        assert!(
+            !project
+                .file("Rocket.toml")
+                .unwrap()
+                .contains("run_migrations")
+        );
+
+        let project = Scaffolder::new("postgres")
+            .var("project_name", "my-api")
+            .feature("migrations")
+            .render()
+            .unwrap();
+        let db = project.file("src/db/mod.rs").unwrap();
+        let migrations = project.file("src/db/migrations.rs").unwrap();
+
+        assert!(db.contains("mod migrations;"));
+        assert!(db.contains("if config.run_migrations"));
+        assert!(migrations.contains(r#"include_dir!("$CARGO_MANIFEST_DIR/migrations")"#));
+        assert!(migrations.contains("schema_migrations"));
+        assert!(
+            project
+                .file("build.rs")
+                .unwrap()
+                .contains("rerun-if-changed=migrations")
+        );
+        assert!(project.file("Cargo.toml").unwrap().contains("include_dir"));
+        assert!(
+            project
+                .file("Rocket.toml")
+                .unwrap()
+                .contains("run_migrations = true")
+        );
+    }
+
+    #[test]
+    fn test_every_template_has_health_and_version_routes() {
+        for template in ["minimal", "mongodb", "postgres"] {
+            let project = render(template);
+            let main = project.file("src/main.rs").unwrap();
+            let health = project.file("src/health/mod.rs").unwrap();
+            let build = project.file("build.rs").unwrap();
+
+            assert!(main.contains("mod health;"));
+            assert!(main.contains(".mount(\"/\", health::routes())"));
+            for route in ["/health/live", "/health/ready", "/version"] {
+                assert!(health.contains(&format!("#[get(\"{}\")]", route)));
+            }
+            assert!(health.contains("env!(\"GIT_SHA\")"));
+            assert!(build.contains("cargo:rustc-env=GIT_SHA="));
+            assert_eq!(health.contains("repo.ping()"), template != "minimal");
+        }
+
+        for template in ["mongodb", "postgres"] {
+            let project = render(template);
+            let repositories = project.file("src/repositories/mod.rs").unwrap();
+            assert!(repositories.contains("async fn ping(&self) -> StoreResult<()>;"));
+        }
+    }
+
+    #[test]
+    fn test_openapi_is_optional() {
+        let project = render("postgres");
+        assert!(project.file("src/openapi/mod.rs").is_none());
+        assert!(!project.file("src/main.rs").unwrap().contains("openapi"));
+
+        for template in ["mongodb", "postgres"] {
+            let project = Scaffolder::new(template)
+                .var("project_name", "my-api")
+                .feature("openapi")
+                .render()
+                .unwrap();
+            let main = project.file("src/main.rs").unwrap();
+            let openapi = project.file("src/openapi/mod.rs").unwrap();
+
+            assert!(main.contains("mod openapi;"));
+            assert!(main.contains(".attach(openapi::fairing())"));
+            assert!(main.contains(".mount(\"/\", openapi::routes())"));
+            assert!(openapi.contains("#[get(\"/openapi.json\")]"));
+            assert!(openapi.contains("fn every_mounted_route_is_described()"));
+            assert!(openapi.contains(&format!(
+                "pub const EXPORT_ENV: &str = \"{}\";",
+                rocket_cli::commands::openapi::EXPORT_ENV
+            )));
+        }
+    }
+
+    #[test]
+    fn test_mongodb_indexes_match_the_cli() {
+        let project = render("mongodb");
+        let db = project.file("src/db/mod.rs").unwrap();
+        let routes = project.file("src/routes/mod.rs").unwrap();
+
+        for index in rocket_cli::db::INDEXES {
+            let definition = format!(
+                "(\"{}\", \"{}\", \"{}\", {})",
+                index.collection, index.name, index.field, index.unique
+            );
+            assert!(db.contains(&definition), "{}", definition);
+        }
+
+        // The generated `UserDocument`, its schema and the CLI's validator share one field list.
+        let models = project.file("src/models/mod.rs").unwrap();
+        assert!(models.contains("include_str!(\"user_schema.json\")"));
+        for spec in rocket_cli::db::USER_FIELDS {
+            let declaration = format!("pub {}: {},", spec.field, spec.rust_type);
+            assert!(models.contains(&declaration), "{}", declaration);
+        }
+        assert!(models.contains("#[serde(rename = \"createdAt\")]"));
+        let schema = project.file("src/models/user_schema.json").unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(schema).unwrap(),
+            rocket_cli::db::user_schema()
+        );
+        #[cfg(feature = "mongodb")]
+        assert_eq!(
+            rocket_cli::db::user_validator(),
+            serde_json::from_str::<mongodb::bson::Document>(schema).unwrap()
+        );
+        assert!(db.contains("sync_schema(&database, config.validate_schema)"));
+        assert!(routes.contains("Err(StoreError::Conflict)"));
+        assert!(
+            project
+                .file("Rocket.toml")
+                .unwrap()
+                .contains("validate_schema = false")
+        );
+    }
+}